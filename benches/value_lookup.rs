@@ -0,0 +1,63 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nt_hive::{Hive, HivePatch, KeyValueDataType, OwnedNtHiveName};
+
+/// Creates a copy of the testhive with a `many-values` key holding `count` values.
+fn hive_with_values(count: usize) -> Vec<u8> {
+    let testhive = std::fs::read("testdata/testhive").unwrap();
+    let hive = Hive::new(testhive.as_ref()).unwrap();
+
+    let mut patch = HivePatch::new();
+    patch.create_key("many-values");
+
+    for i in 0..count {
+        let data = (i as u32).to_le_bytes();
+        patch.set_value(
+            "many-values",
+            &format!("value{i}"),
+            KeyValueDataType::RegDWord,
+            &data,
+        );
+    }
+
+    patch.apply(&hive).unwrap()
+}
+
+fn value_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("value_lookup");
+
+    for count in [10, 100, 500] {
+        let bytes = hive_with_values(count);
+        let hive = Hive::new(bytes.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("many-values").unwrap().unwrap();
+
+        // Look up the last value, which is the worst case for a linear scan.
+        let name = format!("VALUE{}", count - 1);
+
+        group.bench_with_input(BenchmarkId::new("linear", count), &name, |b, name| {
+            b.iter(|| key_node.value(black_box(name)).unwrap().unwrap())
+        });
+
+        let map = key_node.value_map().unwrap();
+        let owned_name = OwnedNtHiveName::from(name.as_str());
+        group.bench_with_input(
+            BenchmarkId::new("value_map", count),
+            &owned_name,
+            |b, name| b.iter(|| map.get(black_box(name)).unwrap().name().unwrap()),
+        );
+
+        group.bench_function(BenchmarkId::new("value_map_build", count), |b| {
+            b.iter(|| key_node.value_map().unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, value_lookup);
+criterion_main!(benches);
@@ -25,16 +25,16 @@ pub(crate) const BIG_DATA_SEGMENT_SIZE: usize = 16344;
 
 /// On-Disk Structure of a Big Data header.
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
-struct BigDataHeader {
-    signature: [u8; 2],
-    segment_count: U16<LittleEndian>,
-    segment_list_offset: U32<LittleEndian>,
+#[repr(C, packed)]
+pub(crate) struct BigDataHeader {
+    pub(crate) signature: [u8; 2],
+    pub(crate) segment_count: U16<LittleEndian>,
+    pub(crate) segment_list_offset: U32<LittleEndian>,
 }
 
 /// On-Disk Structure of a Big Data list item.
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct BigDataListItem {
     segment_offset: U32<LittleEndian>,
 }
@@ -86,6 +86,7 @@ impl BigDataListItemRanges {
         // Verify this header.
         let header_range = byte_subrange(&header_cell_range, mem::size_of::<BigDataHeader>())
             .ok_or_else(|| NtHiveError::InvalidHeaderSize {
+                context: "big data",
                 offset: hive.offset_of_data_offset(header_cell_range.start),
                 expected: mem::size_of::<BigDataHeader>(),
                 actual: header_cell_range.len(),
@@ -100,6 +101,7 @@ impl BigDataListItemRanges {
         let max_data_size = segment_count as usize * BIG_DATA_SEGMENT_SIZE;
         if data_size > max_data_size {
             return Err(NtHiveError::InvalidSizeField {
+                context: "big data",
                 offset: data_size_field_offset,
                 expected: max_data_size,
                 actual: data_size,
@@ -115,6 +117,7 @@ impl BigDataListItemRanges {
 
         let items_range = byte_subrange(&segment_list_cell_range, byte_count).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "big data",
                 offset: hive.offset_of_field(&header.segment_count),
                 expected: byte_count,
                 actual: segment_list_cell_range.len(),
@@ -135,6 +138,7 @@ impl BigDataListItemRanges {
             Ok(())
         } else {
             Err(NtHiveError::InvalidTwoByteSignature {
+                context: "big data",
                 offset: hive.offset_of_field(signature),
                 expected: expected_signature,
                 actual: *signature,
@@ -188,10 +192,17 @@ impl FusedIterator for BigDataListItemRanges {}
 ///   returning a constant byte slice for each item,
 ///   used by [`KeyValueData`].
 ///
+/// Every returned slice corresponds to exactly one Big Data segment.
+/// All segments contain 16344 bytes except for the last one, which contains the remaining bytes.
+/// Concatenating all slices yields the data of the Key Value.
+///
+/// An error only affects the segment it is returned for.
+/// Iteration continues with the next segment afterwards, so the number of returned items
+/// always equals the number of segments.
+///
 /// On-Disk Signature: `db`
 ///
 /// [`KeyValueData`]: crate::key_value::KeyValueData
-#[derive(Clone)]
 pub struct BigDataSlices<'h, B: SplitByteSlice> {
     hive: &'h Hive<B>,
     big_data_list_item_ranges: BigDataListItemRanges,
@@ -202,6 +213,18 @@ impl<'h, B> BigDataSlices<'h, B>
 where
     B: SplitByteSlice,
 {
+    /// Returns the number of data bytes that have not been returned yet.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn bytes_left(&self) -> usize {
+        self.bytes_left
+    }
+
+    /// Returns the number of data bytes in the segment that is returned next.
+    pub(crate) fn next_segment_size(&self) -> usize {
+        // Every segment contains BIG_DATA_SEGMENT_SIZE bytes of data except for the last one.
+        cmp::min(self.bytes_left, BIG_DATA_SEGMENT_SIZE)
+    }
+
     pub(crate) fn new(
         hive: &'h Hive<B>,
         data_size: u32,
@@ -219,6 +242,20 @@ where
     }
 }
 
+// Not derived, because that would needlessly require `B: Clone`.
+impl<B> Clone for BigDataSlices<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hive: self.hive,
+            big_data_list_item_ranges: self.big_data_list_item_ranges.clone(),
+            bytes_left: self.bytes_left,
+        }
+    }
+}
+
 impl<'h, B> Iterator for BigDataSlices<'h, B>
 where
     B: SplitByteSlice,
@@ -226,8 +263,7 @@ where
     type Item = Result<&'h [u8]>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Every segment contains BIG_DATA_SEGMENT_SIZE bytes of data except for the last one.
-        let bytes_to_return = cmp::min(self.bytes_left, BIG_DATA_SEGMENT_SIZE);
+        let bytes_to_return = self.next_segment_size();
         if bytes_to_return == 0 {
             return None;
         }
@@ -242,6 +278,7 @@ where
         let cell_range = iter_try!(self.hive.cell_range_from_data_offset(segment_offset));
         let data_range = iter_try!(byte_subrange(&cell_range, bytes_to_return).ok_or_else(|| {
             NtHiveError::InvalidDataSize {
+                context: "big data segment",
                 offset: self.hive.offset_of_data_offset(cell_range.start),
                 expected: bytes_to_return,
                 actual: cell_range.len(),
@@ -329,4 +366,24 @@ mod tests {
         assert!(matches!(key_value_data, KeyValueData::Big(_)));
         assert_eq!(key_value_data.into_vec().unwrap(), expected_data);
     }
+
+    #[test]
+    fn test_segments() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+
+        // Small data is returned as a single segment.
+        let key_value = key_node.value("B").unwrap().unwrap();
+        let segments = key_value.data().unwrap().segments();
+        let segment_lengths = segments.map(|segment| segment.unwrap().len());
+        assert!(segment_lengths.eq([16344]));
+
+        // Big Data is returned as one full segment followed by the remaining byte.
+        let key_value = key_node.value("C").unwrap().unwrap();
+        let segments = key_value.data().unwrap().segments();
+        let segment_lengths = segments.map(|segment| segment.unwrap().len());
+        assert!(segment_lengths.eq([16344, 1]));
+    }
 }
@@ -5,63 +5,109 @@ use thiserror::Error;
 
 use crate::key_value::KeyValueDataType;
 
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
 /// Central result type of nt-hive.
 pub type Result<T, E = NtHiveError> = core::result::Result<T, E>;
 
 /// Central error type of nt-hive.
 #[derive(Clone, Debug, Error, Eq, PartialEq)]
 pub enum NtHiveError {
+    #[error("The data has a size of {expected} bytes, but the buffer only holds {actual} bytes")]
+    BufferTooSmall { expected: usize, actual: usize },
+    #[error("The root key cannot be deleted")]
+    CannotDeleteRootKey,
+    #[cfg(feature = "alloc")]
+    #[error(
+        "The key value at offset {offset:#010x} has the same name as another value of its key"
+    )]
+    DuplicateValueName { offset: usize },
+    #[error("The base block declares {declared} bytes of hive data, but only {actual} bytes are left in the slice")]
+    HiveTooSmall { declared: u32, actual: usize },
     #[error("The checksum in the base block should be {expected}, but it is {actual}")]
     InvalidChecksum { expected: u32, actual: u32 },
-    #[error("The data at offset {offset:#010x} should have a size of {expected} bytes, but it only has {actual} bytes")]
+    #[error("The {context} at offset {offset:#010x} should have a size of {expected} bytes, but it only has {actual} bytes")]
     InvalidDataSize {
+        context: &'static str,
         offset: usize,
         expected: usize,
         actual: usize,
     },
-    #[error("The 4-byte signature field at offset {offset:#010x} should contain {expected:?}, but it contains {actual:?}")]
+    #[error("The 4-byte signature field of the {context} at offset {offset:#010x} should contain {expected:?}, but it contains {actual:?}")]
     InvalidFourByteSignature {
+        context: &'static str,
         offset: usize,
         expected: &'static [u8],
         actual: [u8; 4],
     },
-    #[error("The struct at offset {offset:#010x} should have a size of {expected} bytes, but only {actual} bytes are left in the slice")]
+    #[error("The {context} at offset {offset:#010x} should have a size of {expected} bytes, but only {actual} bytes are left in the slice")]
     InvalidHeaderSize {
+        context: &'static str,
         offset: usize,
         expected: usize,
         actual: usize,
     },
+    #[error("The blink field at offset {offset:#010x} should point back to the key security cell at data offset {expected:#010x}, but it contains {actual:#010x}")]
+    InvalidKeySecurityBlink {
+        offset: usize,
+        expected: u32,
+        actual: u32,
+    },
     #[error("Expected one of the key value data types {expected:?}, but found {actual:?}")]
     InvalidKeyValueDataType {
         expected: &'static [KeyValueDataType],
         actual: KeyValueDataType,
     },
-    #[error("The size field at offset {offset:#010x} specifies {expected} bytes, but only {actual} bytes are left in the slice")]
+    #[error("The size field of the {context} at offset {offset:#010x} specifies {expected} bytes, but only {actual} bytes are left in the slice")]
     InvalidSizeField {
+        context: &'static str,
         offset: usize,
         expected: usize,
         actual: usize,
     },
-    #[error("The size field at offset {offset:#010x} specifies {size} bytes, but they are not aligned to the expected {expected_alignment} bytes")]
+    #[error("The size field of the {context} at offset {offset:#010x} specifies {size} bytes, but they are not aligned to the expected {expected_alignment} bytes")]
     InvalidSizeFieldAlignment {
+        context: &'static str,
         offset: usize,
         size: usize,
         expected_alignment: usize,
     },
-    #[error("The 2-byte signature field at offset {offset:#010x} should contain {expected:?}, but it contains {actual:?}")]
+    #[error("The 2-byte signature field of the {context} at offset {offset:#010x} should contain {expected:?}, but it contains {actual:?}")]
     InvalidTwoByteSignature {
+        context: &'static str,
         offset: usize,
         expected: &'static [u8],
         actual: [u8; 2],
     },
+    #[cfg(feature = "std")]
+    #[error("An I/O error occurred: {kind}")]
+    Io { kind: std::io::ErrorKind },
+    #[cfg(feature = "alloc")]
+    #[error("The key node at offset {offset:#010x} has already been visited, so the subkeys lists form a cycle")]
+    KeyNodeCycle { offset: usize },
+    #[cfg(feature = "alloc")]
+    #[error("The key {path:?} does not exist")]
+    KeyNotFound { path: String },
+    #[error(
+        "The cell at offset {offset:#010x} overlaps the following cell or exceeds its hive bin"
+    )]
+    OverlappingCells { offset: usize },
     #[error("The sequence numbers in the base block do not match ({primary} != {secondary})")]
     SequenceNumberMismatch { primary: u32, secondary: u32 },
+    #[cfg(feature = "alloc")]
+    #[error("The key value cell at offset {offset:#010x} is referenced by more than one key")]
+    SharedValueCell { offset: usize },
+    #[error("The subkey count field at offset {offset:#010x} specifies {actual} subkeys, which is far more than the maximum of {max} subkeys to visit")]
+    TooManySubkeys {
+        offset: usize,
+        max: usize,
+        actual: u32,
+    },
     #[error("The cell at offset {offset:#010x} with a size of {size} bytes is unallocated")]
     UnallocatedCell { offset: usize, size: i32 },
-    #[error(
-        "The clustering factor in the base block is expected to be {expected}, but it is {actual}"
-    )]
-    UnsupportedClusteringFactor { expected: u32, actual: u32 },
+    #[error("The clustering factor in the base block is expected to be a power of two up to {max}, but it is {actual}")]
+    UnsupportedClusteringFactor { max: u32, actual: u32 },
     #[error("The file format in the base block is expected to be {expected}, but it is {actual}")]
     UnsupportedFileFormat { expected: u32, actual: u32 },
     #[error("The file type in the base block is expected to be {expected}, but it is {actual}")]
@@ -70,4 +116,102 @@ pub enum NtHiveError {
     UnsupportedKeyValueDataType { offset: usize, actual: u32 },
     #[error("The version in the base block ({major}.{minor}) is unsupported")]
     UnsupportedVersion { major: u32, minor: u32 },
+    #[cfg(feature = "alloc")]
+    #[error("The key {path:?} has no value named {name:?}")]
+    ValueNotFound { path: String, name: String },
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for NtHiveError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io { kind: error.kind() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<NtHiveError> for std::io::Error {
+    fn from(error: NtHiveError) -> Self {
+        // Keep the original kind if this error came from an I/O error in the first place.
+        let kind = match error {
+            NtHiveError::Io { kind } => kind,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+
+        std::io::Error::new(kind, error)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_into_io_error() {
+        let error = NtHiveError::InvalidChecksum {
+            expected: 1,
+            actual: 2,
+        };
+        let message = error.to_string();
+        let io_error = std::io::Error::from(error);
+        assert_eq!(io_error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(io_error.to_string(), message);
+
+        // The original hive error is preserved.
+        let inner = io_error.into_inner().unwrap();
+        assert_eq!(
+            inner.downcast_ref::<NtHiveError>(),
+            Some(&NtHiveError::InvalidChecksum {
+                expected: 1,
+                actual: 2
+            })
+        );
+
+        // Errors that originate from I/O errors keep their kind.
+        let error = NtHiveError::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        let io_error = std::io::Error::from(error);
+        assert_eq!(io_error.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_signature_error_context() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        let signature_offset = 4096 + key_value.cell_offset() as usize + 4;
+        testhive[signature_offset..signature_offset + 2].copy_from_slice(b"xx");
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let error = key_node.value("dword").unwrap().err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "The 2-byte signature field of the key value at offset {signature_offset:#010x} \
+                should contain [118, 107], but it contains [120, 120]"
+            )
+        );
+
+        // 4-byte signatures carry their context as well.
+        testhive[..4].copy_from_slice(b"xxxx");
+        let error = Hive::new(testhive.as_ref()).err().unwrap();
+        assert!(matches!(
+            error,
+            NtHiveError::InvalidFourByteSignature {
+                context: "base block",
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("of the base block at offset"));
+
+        // So do size errors.
+        let error = Hive::new(&testhive[..100]).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The base block at offset 0x00000000 should have a size of 4096 bytes, \
+            but only 100 bytes are left in the slice"
+        );
+    }
 }
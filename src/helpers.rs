@@ -35,6 +35,16 @@ pub mod tests {
     use std::fs::File;
     use std::io::Read;
 
+    use crate::hive::Hive;
+
+    /// Recalculates the checksum of a hive whose base block has been modified by a test.
+    pub fn fix_checksum(hive_bytes: &mut [u8]) {
+        let checksum = Hive::without_validation(&*hive_bytes)
+            .unwrap()
+            .calculate_checksum();
+        hive_bytes[508..512].copy_from_slice(&checksum.to_le_bytes());
+    }
+
     pub fn testhive_vec() -> Vec<u8> {
         let mut buffer = Vec::new();
         File::open("testdata/testhive")
@@ -14,12 +14,26 @@ use zerocopy::{
 
 use crate::error::{NtHiveError, Result};
 use crate::helpers::byte_subrange;
+use crate::hive_bin::{HiveBinCells, HiveBins};
 use crate::key_node::{KeyNode, KeyNodeMut};
+use crate::key_security::{security_descriptor_range, SecurityDescriptors};
+use crate::string::NtHiveNameString;
+
+#[cfg(feature = "alloc")]
+use {
+    crate::key_node::KeysBreadthFirst,
+    crate::traversal::{ProgressTraversal, SubtreeKeys, TraversalMode},
+    alloc::collections::{BTreeMap, BTreeSet},
+    alloc::format,
+    alloc::string::String,
+    alloc::vec::Vec,
+    core::fmt,
+};
 
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
-struct CellHeader {
-    size: I32<LittleEndian>,
+#[repr(C, packed)]
+pub(crate) struct CellHeader {
+    pub(crate) size: I32<LittleEndian>,
 }
 
 /// Known hive minor versions.
@@ -38,11 +52,41 @@ pub enum HiveMinorVersion {
     WindowsVista = 6,
 }
 
-#[allow(dead_code)]
+/// Hive format features that depend on the hive version, as checked by [`Hive::supports`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HiveFeature {
+    /// Data bigger than a single segment is split across multiple cells of a Big Data structure.
+    /// Older hives store such data in a single cell.
+    BigData,
+    /// Subkeys lists may be Hash Leafs (`lh`).
+    HashLeafs,
+    /// The base block contains the timestamp of the last reorganization.
+    ReorgStats,
+    /// The base block contains the `RmId`, `LogId`, `TmId`, and `ThawTmId` GUIDs.
+    TransactionIds,
+}
+
+impl HiveFeature {
+    /// Returns the first hive minor version that supports this feature.
+    pub const fn minimum_minor_version(self) -> HiveMinorVersion {
+        match self {
+            Self::BigData => HiveMinorVersion::WindowsXPBeta,
+            Self::HashLeafs => HiveMinorVersion::WindowsXP,
+            Self::ReorgStats => HiveMinorVersion::WindowsVista,
+            Self::TransactionIds => HiveMinorVersion::WindowsXP,
+        }
+    }
+}
+
+/// Known hive file types, as returned by [`Hive::file_type`].
+#[derive(Clone, Copy, Debug, Eq, N, PartialEq)]
 #[repr(u32)]
-enum HiveFileTypes {
+pub enum HiveFileType {
+    /// A regular hive file.
     Primary = 0,
+    /// A transaction log file belonging to a hive.
     Log = 1,
+    /// A standalone hive file exported via `RegSaveKey`, which comes without logs.
     External = 2,
 }
 
@@ -51,33 +95,194 @@ enum HiveFileFormats {
     Memory = 1,
 }
 
-#[allow(dead_code)]
+/// On-Disk Structure of the base block at the beginning of every hive.
+///
+/// Use [`Hive::base_block`] to get it and the getter methods to read its fields.
+///
+/// On-Disk Signature: `regf`
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
-struct HiveBaseBlock {
-    signature: [u8; 4],
-    primary_sequence_number: U32<LittleEndian>,
-    secondary_sequence_number: U32<LittleEndian>,
-    timestamp: U64<LittleEndian>,
-    major_version: U32<LittleEndian>,
-    minor_version: U32<LittleEndian>,
-    file_type: U32<LittleEndian>,
-    file_format: U32<LittleEndian>,
-    root_cell_offset: U32<LittleEndian>,
-    data_size: U32<LittleEndian>,
-    clustering_factor: U32<LittleEndian>,
-    file_name: [U16<LittleEndian>; 32],
-    padding_1: [u8; 396],
-    checksum: U32<LittleEndian>,
-    padding_2: [u8; 3576],
-    boot_type: U32<LittleEndian>,
-    boot_recover: U32<LittleEndian>,
+#[repr(C, packed)]
+pub struct BaseBlock {
+    pub(crate) signature: [u8; 4],
+    pub(crate) primary_sequence_number: U32<LittleEndian>,
+    pub(crate) secondary_sequence_number: U32<LittleEndian>,
+    pub(crate) timestamp: U64<LittleEndian>,
+    pub(crate) major_version: U32<LittleEndian>,
+    pub(crate) minor_version: U32<LittleEndian>,
+    pub(crate) file_type: U32<LittleEndian>,
+    pub(crate) file_format: U32<LittleEndian>,
+    pub(crate) root_cell_offset: U32<LittleEndian>,
+    pub(crate) data_size: U32<LittleEndian>,
+    pub(crate) clustering_factor: U32<LittleEndian>,
+    pub(crate) file_name: [U16<LittleEndian>; 32],
+    pub(crate) rm_id: [u8; 16],
+    pub(crate) log_id: [u8; 16],
+    pub(crate) flags: U32<LittleEndian>,
+    pub(crate) tm_id: [u8; 16],
+    padding_1: [u8; 4],
+    pub(crate) last_reorganized_timestamp: U64<LittleEndian>,
+    padding_2: [u8; 332],
+    pub(crate) checksum: U32<LittleEndian>,
+    padding_3: [u8; 3528],
+    pub(crate) thaw_tm_id: [u8; 16],
+    padding_4: [u8; 32],
+    pub(crate) boot_type: U32<LittleEndian>,
+    pub(crate) boot_recover: U32<LittleEndian>,
+}
+
+impl BaseBlock {
+    /// Returns the value of the `boot_recover` field.
+    pub fn boot_recover(&self) -> u32 {
+        self.boot_recover.get()
+    }
+
+    /// Returns the value of the `boot_type` field.
+    pub fn boot_type(&self) -> u32 {
+        self.boot_type.get()
+    }
+
+    /// Returns the checksum stored in the base block.
+    ///
+    /// This is an XOR-32 checksum over the preceding 508 bytes of the base block.
+    pub fn checksum(&self) -> u32 {
+        self.checksum.get()
+    }
+
+    /// Returns the clustering factor of the hive.
+    pub fn clustering_factor(&self) -> u32 {
+        self.clustering_factor.get()
+    }
+
+    /// Returns the size of the hive data following the base block, in bytes.
+    pub fn data_size(&self) -> u32 {
+        self.data_size.get()
+    }
+
+    /// Returns the raw value of the `file_format` field.
+    pub fn file_format(&self) -> u32 {
+        self.file_format.get()
+    }
+
+    /// Returns the file name stored in the base block, without padding.
+    ///
+    /// This usually contains the last characters of the path the hive was loaded from.
+    pub fn file_name(&self) -> NtHiveNameString<'_> {
+        NtHiveNameString::Utf16LE(self.file_name.as_bytes()).trim_end_nul()
+    }
+
+    /// Returns the raw value of the `file_type` field.
+    pub fn file_type(&self) -> u32 {
+        self.file_type.get()
+    }
+
+    /// Returns the major version of the hive.
+    pub fn major_version(&self) -> u32 {
+        self.major_version.get()
+    }
+
+    /// Returns the minor version of the hive.
+    pub fn minor_version(&self) -> u32 {
+        self.minor_version.get()
+    }
+
+    /// Returns the primary sequence number.
+    pub fn primary_sequence_number(&self) -> u32 {
+        self.primary_sequence_number.get()
+    }
+
+    /// Returns the data offset of the root [`KeyNode`].
+    pub fn root_cell_offset(&self) -> u32 {
+        self.root_cell_offset.get()
+    }
+
+    /// Returns the secondary sequence number.
+    pub fn secondary_sequence_number(&self) -> u32 {
+        self.secondary_sequence_number.get()
+    }
+
+    /// Returns the signature of the base block, which is `regf` for valid hives.
+    pub fn signature(&self) -> [u8; 4] {
+        self.signature
+    }
+
+    /// Returns the timestamp of the last write to the hive in the FILETIME format.
+    /// i.e. the number of 100-nanosecond intervals since January 1, 1601 (UTC).
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp.get()
+    }
+}
+
+/// Printable summary of the most important base block fields, returned by [`Hive::base_block_info`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BaseBlockInfo {
+    /// Signature of the base block (lossily decoded), which is `regf` for valid hives.
+    pub signature: String,
+    /// Major version of the hive format, which is 1 for all known hives.
+    pub major_version: u32,
+    /// Minor version of the hive format, see [`HiveMinorVersion`].
+    pub minor_version: u32,
+    /// Sequence number incremented before writing to the hive.
+    pub primary_sequence_number: u32,
+    /// Sequence number incremented after writing to the hive, which equals the primary one
+    /// for a consistent hive.
+    pub secondary_sequence_number: u32,
+    /// Sector size of the underlying disk divided by 512.
+    pub clustering_factor: u32,
+    /// Data offset of the root [`KeyNode`].
+    pub root_cell_offset: u32,
+    /// Size of the hive data following the base block, in bytes.
+    pub data_size: u32,
+    /// File name stored in the base block, without padding.
+    pub file_name: String,
+    /// Whether the stored checksum matches the calculated one.
+    pub checksum_valid: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for BaseBlockInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Signature: {}", self.signature)?;
+        writeln!(f, "Version: {}.{}", self.major_version, self.minor_version)?;
+        writeln!(
+            f,
+            "Sequence numbers: {} / {}",
+            self.primary_sequence_number, self.secondary_sequence_number
+        )?;
+        writeln!(f, "Clustering factor: {}", self.clustering_factor)?;
+        writeln!(f, "Root cell offset: {:#010x}", self.root_cell_offset)?;
+        writeln!(f, "Data size: {}", self.data_size)?;
+        writeln!(f, "File name: {}", self.file_name)?;
+
+        let checksum = if self.checksum_valid {
+            "valid"
+        } else {
+            "invalid"
+        };
+        write!(f, "Checksum: {checksum}")
+    }
 }
 
 /// Root structure describing a registry hive.
 pub struct Hive<B: SplitByteSlice> {
-    base_block: Ref<B, HiveBaseBlock>,
+    pub(crate) base_block: Ref<B, BaseBlock>,
     pub(crate) data: B,
+    /// Offset of the hive within the bytes passed to [`Hive::new_at_offset`].
+    base_offset: usize,
+    /// Length of the bytes starting at the base block, including any trailing bytes
+    /// that have been cut off `data`.
+    buffer_len: usize,
+}
+
+impl Hive<&[u8]> {
+    /// Returns the offset of the first `regf` signature in `bytes`, which may indicate a hive
+    /// embedded in a larger blob.
+    ///
+    /// The signature alone doesn't guarantee a valid hive.
+    /// Pass the returned offset to [`Hive::new_at_offset`] to validate and open it.
+    pub fn find_signature(bytes: &[u8]) -> Option<usize> {
+        bytes.windows(4).position(|window| window == b"regf")
+    }
 }
 
 impl<B> Hive<B>
@@ -87,11 +292,14 @@ where
     /// Creates a new `Hive` from any byte slice.
     /// Performs basic validation and rejects any invalid hive.
     ///
+    /// Any bytes following the hive data declared in the base block (e.g. slack space of a disk image)
+    /// are ignored, see [`Hive::declared_len`] and [`Hive::buffer_len`].
+    ///
     /// You may use [`Hive::without_validation`] if you want to accept hives that fail validation.
     pub fn new(bytes: B) -> Result<Self> {
         let hive = Self::without_validation(bytes)?;
         hive.validate()?;
-        Ok(hive)
+        Ok(hive.without_trailing_bytes())
     }
 
     /// Creates a new `Hive` from any byte slice, without validating the header.
@@ -99,19 +307,92 @@ where
     /// You may later validate the header via [`Hive::validate`].
     /// This is a solution for accessing parts of hives that have not been fully flushed to disk
     /// (e.g. due to hibernation and mismatching sequence numbers).
+    ///
+    /// As the declared hive data size can't be trusted here, all bytes following the base block
+    /// are treated as hive data.
     pub fn without_validation(bytes: B) -> Result<Self> {
         let length = bytes.len();
         let (base_block, data) =
             Ref::from_prefix(bytes).map_err(|_| NtHiveError::InvalidHeaderSize {
+                context: "base block",
                 offset: 0,
-                expected: mem::size_of::<HiveBaseBlock>(),
+                expected: mem::size_of::<BaseBlock>(),
                 actual: length,
             })?;
 
-        let hive = Self { base_block, data };
+        let hive = Self {
+            base_block,
+            data,
+            base_offset: 0,
+            buffer_len: length,
+        };
+        Ok(hive)
+    }
+
+    /// Creates a new `Hive` from a byte slice that contains the hive at the given offset,
+    /// e.g. a memory dump or a container format.
+    /// Performs basic validation and rejects any invalid hive.
+    ///
+    /// All offsets reported in errors are relative to the beginning of `bytes`.
+    /// Use [`Hive::find_signature`] to find the offset of a hive.
+    pub fn new_at_offset(bytes: B, offset: usize) -> Result<Self> {
+        let length = bytes.len();
+        let (_, bytes) = bytes
+            .split_at(offset)
+            .map_err(|_| NtHiveError::InvalidHeaderSize {
+                context: "base block",
+                offset,
+                expected: mem::size_of::<BaseBlock>(),
+                actual: length.saturating_sub(offset),
+            })?;
+
+        let mut hive = Self::without_validation(bytes).map_err(|e| match e {
+            NtHiveError::InvalidHeaderSize {
+                context,
+                offset: header_offset,
+                expected,
+                actual,
+            } => NtHiveError::InvalidHeaderSize {
+                context,
+                offset: offset + header_offset,
+                expected,
+                actual,
+            },
+            e => e,
+        })?;
+        hive.base_offset = offset;
+        hive.validate()?;
+        Ok(hive.without_trailing_bytes())
+    }
+
+    /// Creates a `Hive` from the base block of a larger hive followed by a single cell of it,
+    /// which has been read from `data_offset` of the larger hive.
+    ///
+    /// The cell is then at data offset 0, but all offsets reported in errors refer to the
+    /// larger hive.
+    /// This lets [`HiveReader`] share all parsing code without having the entire hive in memory.
+    ///
+    /// [`HiveReader`]: crate::reader::HiveReader
+    #[cfg(feature = "std")]
+    pub(crate) fn from_single_cell(bytes: B, data_offset: u32) -> Result<Self> {
+        let mut hive = Self::without_validation(bytes)?;
+        hive.base_offset = data_offset as usize;
         Ok(hive)
     }
 
+    /// Cuts off all bytes following the declared hive data, so that no offset can resolve to them.
+    ///
+    /// Must only be called after [`Hive::validate_data_size`] has succeeded.
+    fn without_trailing_bytes(self) -> Self {
+        let data_size = self.base_block.data_size.get() as usize;
+        let data = match self.data.split_at(data_size) {
+            Ok((data, _trailing_bytes)) => data,
+            Err(data) => data,
+        };
+
+        Self { data, ..self }
+    }
+
     pub(crate) fn cell_range_from_data_offset(&self, data_offset: u32) -> Result<Range<usize>> {
         // Only valid data offsets are accepted here.
         assert!(data_offset != u32::MAX);
@@ -124,6 +405,7 @@ where
         let remaining_range = data_offset..self.data.len();
         let header_range = byte_subrange(&remaining_range, mem::size_of::<CellHeader>())
             .ok_or_else(|| NtHiveError::InvalidHeaderSize {
+                context: "cell",
                 offset: self.offset_of_data_offset(data_offset),
                 expected: mem::size_of::<CellHeader>(),
                 actual: remaining_range.len(),
@@ -147,6 +429,7 @@ where
         let expected_alignment = 8;
         if cell_size % expected_alignment != 0 {
             return Err(NtHiveError::InvalidSizeFieldAlignment {
+                context: "cell",
                 offset: self.offset_of_field(&header.size),
                 size: cell_size,
                 expected_alignment,
@@ -158,6 +441,7 @@ where
         let cell_data_size = cell_size - mem::size_of::<CellHeader>();
         let cell_data_range = byte_subrange(&remaining_range, cell_data_size).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "cell",
                 offset: self.offset_of_field(&header.size),
                 expected: cell_data_size,
                 actual: remaining_range.len(),
@@ -167,65 +451,473 @@ where
         Ok(cell_data_range)
     }
 
+    /// Returns the parsed [`BaseBlock`] of this hive.
+    pub fn base_block(&self) -> &BaseBlock {
+        &self.base_block
+    }
+
+    /// Returns the raw bytes of the base block of this hive.
+    ///
+    /// These are the first 4096 bytes of the hive.
+    /// Only the first 508 bytes are covered by the checksum.
+    pub fn base_block_bytes(&self) -> &[u8] {
+        Ref::bytes(&self.base_block)
+    }
+
+    /// Returns a printable summary of the most important base block fields.
+    ///
+    /// This is meant for quick diagnostics.
+    /// Use [`Hive::base_block`] to access individual fields.
+    #[cfg(feature = "alloc")]
+    pub fn base_block_info(&self) -> BaseBlockInfo {
+        let base_block = self.base_block();
+
+        BaseBlockInfo {
+            signature: String::from_utf8_lossy(&base_block.signature()).into_owned(),
+            major_version: base_block.major_version(),
+            minor_version: base_block.minor_version(),
+            primary_sequence_number: base_block.primary_sequence_number(),
+            secondary_sequence_number: base_block.secondary_sequence_number(),
+            clustering_factor: base_block.clustering_factor(),
+            root_cell_offset: base_block.root_cell_offset(),
+            data_size: base_block.data_size(),
+            file_name: base_block.file_name().to_string_lossy(),
+            checksum_valid: base_block.checksum() == self.calculate_checksum(),
+        }
+    }
+
     /// Calculate a field's offset from the very beginning of the hive bytes.
     ///
     /// Note that this function primarily exists to provide absolute hive file offsets when reporting errors.
     /// It cannot be used to index into the hive bytes, because they are initially split into `base_block`
     /// and `data`.
-    pub(crate) fn offset_of_field<T>(&self, field: &T) -> usize {
-        let field_address = field as *const T as usize;
+    ///
+    /// `field` may also be a slice of the hive bytes, in which case the offset of its first byte is returned.
+    pub(crate) fn offset_of_field<T: ?Sized>(&self, field: &T) -> usize {
+        let field_address = field as *const T as *const u8 as usize;
         let base_address = Ref::bytes(&self.base_block).as_ptr() as usize;
 
-        assert!(field_address > base_address);
-        field_address - base_address
+        assert!(field_address >= base_address);
+        self.base_offset + (field_address - base_address)
     }
 
     /// Calculate a data offset's offset from the very beginning of the hive bytes.
     pub(crate) fn offset_of_data_offset(&self, data_offset: usize) -> usize {
-        data_offset + mem::size_of::<HiveBaseBlock>()
+        data_offset
+            .saturating_add(mem::size_of::<BaseBlock>())
+            .saturating_add(self.base_offset)
+    }
+
+    /// Returns the length of the bytes backing this hive, starting at the base block.
+    ///
+    /// This may be larger than [`Hive::declared_len`] if the hive is followed by unrelated bytes.
+    /// These trailing bytes are never interpreted as hive data.
+    pub fn buffer_len(&self) -> usize {
+        self.buffer_len
+    }
+
+    /// Returns the clustering factor of this hive.
+    ///
+    /// This is the logical sector size of the disk the hive was written to, divided by 512.
+    /// It is usually `1`, but `8` for disks with 4K sectors.
+    /// Cell offsets are always relative to the first Hive Bin and don't depend on it.
+    pub fn clustering_factor(&self) -> u32 {
+        self.base_block.clustering_factor()
+    }
+
+    /// Returns all `ControlSetNNN` keys at the top level of this hive along with their numbers,
+    /// sorted by number.
+    ///
+    /// This is only meaningful for SYSTEM hives.
+    /// Keys starting with `ControlSet` but not followed by a decimal number are ignored.
+    #[cfg(feature = "alloc")]
+    pub fn control_sets(&self) -> Result<Vec<(u32, KeyNode<'_, B>)>> {
+        const PREFIX: &str = "ControlSet";
+        let mut control_sets = Vec::new();
+
+        let root_key_node = self.root_key_node()?;
+        let Some(subkeys) = root_key_node.subkeys() else {
+            return Ok(control_sets);
+        };
+
+        for key_node in subkeys? {
+            let key_node = key_node?;
+            let name = key_node.name()?.to_string_lossy();
+
+            let Some(number) = name
+                .get(..PREFIX.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(PREFIX))
+                .map(|_| &name[PREFIX.len()..])
+            else {
+                continue;
+            };
+
+            if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+                continue;
+            }
+
+            if let Ok(number) = number.parse::<u32>() {
+                control_sets.push((number, key_node));
+            }
+        }
+
+        control_sets.sort_by_key(|(number, _)| *number);
+        Ok(control_sets)
+    }
+
+    /// Returns the control set [`KeyNode`] that `CurrentControlSet` refers to.
+    ///
+    /// This is only meaningful for SYSTEM hives.
+    /// The control set is selected by the `Current` value of the `Select` key, e.g. a value of 1
+    /// refers to `ControlSet001`.
+    /// Returns `None` if the `Select` key, its `Current` value, or the referenced control set
+    /// doesn't exist.
+    #[cfg(feature = "alloc")]
+    pub fn current_control_set(&self) -> Option<Result<KeyNode<'_, B>>> {
+        let root_key_node = iter_try!(self.root_key_node());
+        let select_key_node = iter_try!(root_key_node.subkey("Select")?);
+        let current_value = iter_try!(select_key_node.value("Current")?);
+        let current = iter_try!(current_value.dword_data());
+
+        let control_set_name = format!("ControlSet{current:03}");
+        root_key_node.subkey(&control_set_name)
+    }
+
+    /// Traverses all values of this hive and counts them by their raw data type code.
+    ///
+    /// Unknown data type codes are counted as well, which makes this useful for finding out
+    /// what kind of data an unfamiliar hive contains.
+    #[cfg(feature = "alloc")]
+    pub fn data_type_counts(&self) -> Result<BTreeMap<u32, usize>> {
+        let mut counts = BTreeMap::new();
+
+        for key_value in self.root_key_node()?.all_values(TraversalMode::Strict) {
+            let (_, data_type_code) = key_value?.data_type_full();
+            *counts.entry(data_type_code).or_insert(0) += 1;
+        }
+
+        Ok(counts)
+    }
+
+    /// Returns the length of this hive as declared in its base block, i.e. the size of the base block
+    /// plus the declared size of the hive data.
+    ///
+    /// Offsets are only resolved within this length.
+    /// See [`Hive::buffer_len`] for the length of the bytes actually backing this hive.
+    pub fn declared_len(&self) -> usize {
+        mem::size_of::<BaseBlock>().saturating_add(self.base_block.data_size.get() as usize)
+    }
+
+    /// Returns the file type of this hive or `None` if it is unknown.
+    pub fn file_type(&self) -> Option<HiveFileType> {
+        HiveFileType::n(self.base_block.file_type())
+    }
+
+    /// Returns an iterator over all keys of this hive for which `pred` returns `true`,
+    /// each along with its backslash-separated path relative to the root key.
+    ///
+    /// This is useful for rule-based scanning, e.g. for keys with a certain value and subkey.
+    /// The path is empty for the root key itself.
+    /// See [`Hive::find_keys_named`] for details on the traversal.
+    #[cfg(feature = "alloc")]
+    pub fn find_keys<'a, F>(
+        &'a self,
+        mut pred: F,
+    ) -> impl Iterator<Item = Result<(String, KeyNode<'a, B>)>> + 'a
+    where
+        F: FnMut(&KeyNode<'a, B>) -> bool + 'a,
+    {
+        self.find_keys_internal(move |key_node| Ok(pred(key_node)), true)
+    }
+
+    /// Returns an iterator over all keys of this hive named `name` (compared case-insensitively),
+    /// no matter where they are located.
+    ///
+    /// Keys are visited depth-first in hive order, starting at the root key.
+    /// The iteration stops after the first error, as the remaining keys may be unreachable.
+    /// Use [`Hive::find_keys_named_with_paths`] to also get the path of each key.
+    #[cfg(feature = "alloc")]
+    pub fn find_keys_named(&self, name: &str) -> impl Iterator<Item = Result<KeyNode<'_, B>>> + '_ {
+        self.find_keys_named_internal(name, false)
+            .map(|item| item.map(|(_, key_node)| key_node))
+    }
+
+    /// Returns an iterator over all keys of this hive named `name` (compared case-insensitively),
+    /// each along with its backslash-separated path relative to the root key.
+    ///
+    /// The path is empty for the root key itself.
+    /// See [`Hive::find_keys_named`] for details.
+    #[cfg(feature = "alloc")]
+    pub fn find_keys_named_with_paths(
+        &self,
+        name: &str,
+    ) -> impl Iterator<Item = Result<(String, KeyNode<'_, B>)>> + '_ {
+        self.find_keys_named_internal(name, true)
+    }
+
+    /// Calls `f` for every key of this hive, starting at the root key and continuing depth-first.
+    ///
+    /// After every `every` keys, `progress` is called with the number of keys and values
+    /// visited so far, which is useful for displaying the progress of a full-hive scan.
+    /// The traversal stops at the first error, either from the hive or returned by `f`.
+    ///
+    /// See [`ProgressTraversal`] for an iterator version.
+    ///
+    /// [`ProgressTraversal`]: crate::traversal::ProgressTraversal
+    #[cfg(feature = "alloc")]
+    pub fn for_each_key_with_progress<F, P>(
+        &self,
+        every: usize,
+        progress: P,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(KeyNode<'_, B>) -> Result<()>,
+        P: FnMut(usize, usize),
+    {
+        for key_node in self.keys_with_progress(every, progress)? {
+            f(key_node?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the total size of all free cells in all Hive Bins, including their cell headers.
+    ///
+    /// Together with [`Hive::used_space`], this adds up to the hive data size minus the headers
+    /// of all Hive Bins.
+    pub fn free_space(&self) -> Result<usize> {
+        self.cells_size(false)
+    }
+
+    /// Returns an iterator over all Hive Bins of this hive, in the order they are stored.
+    pub fn hive_bins(&self) -> HiveBins<'_, B> {
+        HiveBins::new(self)
+    }
+
+    /// Returns `true` if this hive is a standalone snapshot exported via `RegSaveKey`
+    /// (i.e. its file type is [`HiveFileType::External`]).
+    pub fn is_snapshot(&self) -> bool {
+        self.file_type() == Some(HiveFileType::External)
+    }
+
+    /// Returns the [`KeyNode`] at the given data offset, as returned by [`KeyNode::offset`].
+    ///
+    /// The offset is validated to point to an allocated cell holding a Key Node.
+    pub fn key_at(&self, offset: u32) -> Result<KeyNode<'_, B>> {
+        self.check_cell_offset(offset, "key node")?;
+        let cell_range = self.cell_range_from_data_offset(offset)?;
+        KeyNode::from_cell_range(self, cell_range)
+    }
+
+    /// Returns an iterator over all keys of this hive in breadth-first order.
+    ///
+    /// The root key comes first, followed by its subkeys, then all keys of the next level,
+    /// and so on.
+    #[cfg(feature = "alloc")]
+    pub fn keys_breadth_first(&self) -> KeysBreadthFirst<'_, B> {
+        KeysBreadthFirst::new(self)
+    }
+
+    /// Returns a [`ProgressTraversal`] over all keys of this hive, starting at the root key and
+    /// continuing depth-first.
+    ///
+    /// After every `every` keys, `progress` is called with the number of keys and values
+    /// visited so far.
+    ///
+    /// [`ProgressTraversal`]: crate::traversal::ProgressTraversal
+    #[cfg(feature = "alloc")]
+    pub fn keys_with_progress<P>(
+        &self,
+        every: usize,
+        progress: P,
+    ) -> Result<ProgressTraversal<'_, B, P>>
+    where
+        P: FnMut(usize, usize),
+    {
+        let root_key_node = self.root_key_node()?;
+        Ok(ProgressTraversal::new(root_key_node, every, progress))
+    }
+
+    /// Returns the timestamp of the last reorganization of this hive in the FILETIME format.
+    ///
+    /// This field only exists since hive version 1.6.
+    /// It is zero if the hive has never been reorganized.
+    pub fn last_reorganized(&self) -> Option<u64> {
+        self.supports(HiveFeature::ReorgStats)
+            .then(|| self.base_block.last_reorganized_timestamp.get())
+    }
+
+    /// Returns the GUID of the log files belonging to this hive (`LogId`).
+    ///
+    /// This field only exists since hive version 1.5.
+    pub fn log_id(&self) -> Option<[u8; 16]> {
+        self.supports(HiveFeature::TransactionIds)
+            .then_some(self.base_block.log_id)
     }
 
     /// Returns the major version of this hive.
     ///
     /// The only known value is `1`.
     pub fn major_version(&self) -> u32 {
-        self.base_block.major_version.get()
+        self.base_block.major_version()
     }
 
     /// Returns the minor version of this hive.
     ///
     /// You can feed this value to [`HiveMinorVersion::n`] to find out whether this is a known version.
     pub fn minor_version(&self) -> u32 {
-        self.base_block.minor_version.get()
+        self.base_block.minor_version()
+    }
+
+    /// Returns the GUID of the Resource Manager of this hive (`RmId`).
+    ///
+    /// This field only exists since hive version 1.5.
+    pub fn rm_id(&self) -> Option<[u8; 16]> {
+        self.supports(HiveFeature::TransactionIds)
+            .then_some(self.base_block.rm_id)
     }
 
     /// Returns the root [`KeyNode`] of this hive.
-    pub fn root_key_node(&self) -> Result<KeyNode<B>> {
+    pub fn root_key_node(&self) -> Result<KeyNode<'_, B>> {
         let root_cell_offset = self.base_block.root_cell_offset.get();
         let cell_range = self.cell_range_from_data_offset(root_cell_offset)?;
         KeyNode::from_cell_range(self, cell_range)
     }
 
+    /// Returns the raw security descriptor (`SECURITY_DESCRIPTOR_RELATIVE`) stored in the Key
+    /// Security cell at the given data offset, as returned by [`KeyNode::security_offset`].
+    ///
+    /// Many keys usually share a single Key Security cell.
+    /// As the returned slice points into the hive data, resolving a shared cell again is cheap
+    /// and yields the identical slice, so callers don't need to cache anything.
+    pub fn security_descriptor_for(&self, offset: u32) -> Result<&[u8]> {
+        self.check_cell_offset(offset, "key security")?;
+        let cell_range = self.cell_range_from_data_offset(offset)?;
+        let security_descriptor_range = security_descriptor_range(self, cell_range)?;
+        Ok(&self.data[security_descriptor_range])
+    }
+
+    /// Returns an iterator over the raw security descriptors of all Key Security cells of this hive.
+    ///
+    /// Key Security cells form a circular doubly-linked list, which is walked from the cell of the
+    /// root key until it returns there.
+    /// As keys with the same security settings share a cell, every distinct security descriptor
+    /// is returned only once.
+    /// A broken list (e.g. looping without returning to the first cell) is reported as
+    /// [`NtHiveError::InvalidKeySecurityBlink`].
+    pub fn security_descriptors(&self) -> Result<impl Iterator<Item = Result<&[u8]>> + '_> {
+        let root_key_node = self.root_key_node()?;
+        Ok(SecurityDescriptors::new(
+            self,
+            root_key_node.security_offset(),
+        ))
+    }
+
+    /// Returns `len` bytes of hive data starting at the given data offset, after checking that
+    /// they are within the hive data.
+    ///
+    /// Data offsets are relative to the end of the base block, just like all offsets stored in
+    /// the hive and the one returned by [`KeyNode::offset`].
+    /// This allows external code to parse cells that are not supported by this crate.
+    pub fn slice(&self, offset: usize, len: usize) -> Result<&[u8]> {
+        let remaining_range = offset..self.data.len();
+        let range =
+            byte_subrange(&remaining_range, len).ok_or_else(|| NtHiveError::InvalidDataSize {
+                context: "slice",
+                offset: self.offset_of_data_offset(offset),
+                expected: len,
+                actual: remaining_range.len(),
+            })?;
+
+        Ok(&self.data[range])
+    }
+
+    /// Checks whether the version of this hive supports the given format feature.
+    ///
+    /// Accessors for version-dependent base block fields use this to return `None` for older hives
+    /// instead of interpreting reserved bytes.
+    pub fn supports(&self, feature: HiveFeature) -> bool {
+        self.minor_version() >= feature.minimum_minor_version() as u32
+    }
+
+    /// Returns the GUID of the Transaction Manager that was used when the hive was last thawed (`ThawTmId`).
+    ///
+    /// This field only exists since hive version 1.5.
+    pub fn thaw_tm_id(&self) -> Option<[u8; 16]> {
+        self.supports(HiveFeature::TransactionIds)
+            .then_some(self.base_block.thaw_tm_id)
+    }
+
+    /// Returns the GUID of the Transaction Manager of this hive (`TmId`).
+    ///
+    /// This field only exists since hive version 1.5.
+    pub fn tm_id(&self) -> Option<[u8; 16]> {
+        self.supports(HiveFeature::TransactionIds)
+            .then_some(self.base_block.tm_id)
+    }
+
+    /// Returns the total size of all allocated cells in all Hive Bins, including their cell headers.
+    ///
+    /// See [`Hive::free_space`] for the complement.
+    pub fn used_space(&self) -> Result<usize> {
+        self.cells_size(true)
+    }
+
     /// Performs basic validations on the header of this hive.
     ///
     /// If you read the hive via [`Hive::new`], these validations have already been performed.
     /// This function is only relevant for hives opened via [`Hive::without_validation`].
+    /// See [`Hive::validate_cells`] and [`Hive::validate_value_cells`] for deeper checks that need
+    /// to walk the entire hive.
     pub fn validate(&self) -> Result<()> {
-        self.validate_signature()?;
-        self.validate_sequence_numbers()?;
-        self.validate_version()?;
-        self.validate_file_type()?;
-        self.validate_file_format()?;
+        self.validate_base_block()?;
         self.validate_data_size()?;
-        self.validate_clustering_factor()?;
-        self.validate_checksum()?;
         Ok(())
     }
 
-    fn validate_checksum(&self) -> Result<()> {
-        let checksum_offset = offset_of!(HiveBaseBlock, checksum);
+    /// Checks that the cells of each Hive Bin are contiguous and neither overlap nor exceed
+    /// their Hive Bin.
+    ///
+    /// Overlapping cells can be used by a crafted hive to alias data.
+    /// This check walks all cells of the hive and is therefore not part of [`Hive::validate`].
+    pub fn validate_cells(&self) -> Result<()> {
+        HiveBinCells::new(self).try_for_each(|cell| cell.map(|_| ()))
+    }
+
+    /// Checks that no Key Value cell is referenced more than once by the values lists of all keys.
+    ///
+    /// Windows never shares Key Value cells, but a crafted hive can make the values lists of
+    /// two keys overlap, so that the same value appears under both keys.
+    /// This check walks all keys of the hive and is therefore not part of [`Hive::validate`].
+    #[cfg(feature = "alloc")]
+    pub fn validate_value_cells(&self) -> Result<()> {
+        let mut key_value_offsets = BTreeSet::new();
+
+        for key_node in self.keys_breadth_first() {
+            let key_node = key_node?;
+            let Some(key_values) = key_node.values() else {
+                continue;
+            };
+
+            for key_value in key_values? {
+                let offset = key_value?.cell_offset();
+
+                if !key_value_offsets.insert(offset) {
+                    return Err(NtHiveError::SharedValueCell {
+                        offset: self.offset_of_data_offset(offset as usize),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calculates the XOR-32 checksum of all base block bytes preceding the checksum field.
+    pub(crate) fn calculate_checksum(&self) -> u32 {
+        let checksum_offset = offset_of!(BaseBlock, checksum);
 
-        // Calculate the XOR-32 checksum of all bytes preceding the checksum field.
         let mut calculated_checksum = 0;
         for dword_bytes in
             Ref::bytes(&self.base_block)[..checksum_offset].chunks(mem::size_of::<u32>())
@@ -240,7 +932,102 @@ where
             calculated_checksum -= 1;
         }
 
+        calculated_checksum
+    }
+
+    /// Sums up the sizes of all allocated or all free cells.
+    fn cells_size(&self, allocated: bool) -> Result<usize> {
+        HiveBinCells::new(self).try_fold(0, |size, cell| {
+            let cell = cell?;
+            Ok(if cell.allocated == allocated {
+                size + cell.size
+            } else {
+                size
+            })
+        })
+    }
+
+    /// Rejects a data offset passed by the caller if it lies outside the hive data.
+    /// This includes `u32::MAX`, which denotes a missing cell.
+    fn check_cell_offset(&self, offset: u32, context: &'static str) -> Result<()> {
+        if offset as usize >= self.data.len() {
+            return Err(NtHiveError::InvalidHeaderSize {
+                context,
+                offset: self.offset_of_data_offset(offset as usize),
+                expected: mem::size_of::<CellHeader>(),
+                actual: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "alloc")]
+    fn find_keys_internal<'a, F>(
+        &'a self,
+        mut pred: F,
+        with_paths: bool,
+    ) -> impl Iterator<Item = Result<(String, KeyNode<'a, B>)>> + 'a
+    where
+        F: FnMut(&KeyNode<'a, B>) -> Result<bool> + 'a,
+    {
+        let (root_error, keys) = match self.root_key_node() {
+            Ok(root_key_node) => (
+                None,
+                Some(SubtreeKeys::new(
+                    root_key_node,
+                    TraversalMode::Strict,
+                    true,
+                    with_paths,
+                )),
+            ),
+            Err(e) => (Some(Err(e)), None),
+        };
+
+        root_error
+            .into_iter()
+            .chain(keys.into_iter().flatten().filter_map(move |item| {
+                let (path, key_node) = match item {
+                    Ok(item) => item,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                match pred(&key_node) {
+                    Ok(true) => Some(Ok((path, key_node))),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            }))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn find_keys_named_internal(
+        &self,
+        name: &str,
+        with_paths: bool,
+    ) -> impl Iterator<Item = Result<(String, KeyNode<'_, B>)>> + '_ {
+        let name = String::from(name);
+        self.find_keys_internal(
+            move |key_node| Ok(key_node.name()? == name.as_str()),
+            with_paths,
+        )
+    }
+
+    /// Performs all header validations that don't need the hive data following the base block.
+    pub(crate) fn validate_base_block(&self) -> Result<()> {
+        self.validate_signature()?;
+        self.validate_sequence_numbers()?;
+        self.validate_version()?;
+        self.validate_file_type()?;
+        self.validate_file_format()?;
+        self.validate_clustering_factor()?;
+        self.validate_checksum()?;
+        Ok(())
+    }
+
+    fn validate_checksum(&self) -> Result<()> {
         // Compare the calculated checksum with the stored one.
+        let calculated_checksum = self.calculate_checksum();
         let checksum = self.base_block.checksum.get();
         if checksum == calculated_checksum {
             Ok(())
@@ -254,40 +1041,45 @@ where
 
     fn validate_clustering_factor(&self) -> Result<()> {
         let clustering_factor = self.base_block.clustering_factor.get();
-        let expected_clustering_factor = 1;
 
-        if clustering_factor == expected_clustering_factor {
+        // The clustering factor is the sector size divided by 512.
+        // Disks with 4K sectors lead to a clustering factor of 8, but any power of two up to
+        // 64K sectors is plausible.
+        let max_clustering_factor = 128;
+        if clustering_factor.is_power_of_two() && clustering_factor <= max_clustering_factor {
             Ok(())
         } else {
             Err(NtHiveError::UnsupportedClusteringFactor {
-                expected: expected_clustering_factor,
+                max: max_clustering_factor,
                 actual: clustering_factor,
             })
         }
     }
 
-    fn validate_data_size(&self) -> Result<()> {
-        let data_size = self.base_block.data_size.get() as usize;
+    pub(crate) fn validate_data_size(&self) -> Result<()> {
+        let declared = self.base_block.data_size.get();
         let expected_alignment = 4096;
 
+        // Reject sizes going beyond our hive data first, so that all later offset calculations
+        // stay within bounds.
+        let data_size = usize::try_from(declared)
+            .ok()
+            .filter(|data_size| *data_size <= self.data.len())
+            .ok_or(NtHiveError::HiveTooSmall {
+                declared,
+                actual: self.data.len(),
+            })?;
+
         // The data size must be a multiple of 4096 bytes
         if data_size % expected_alignment != 0 {
             return Err(NtHiveError::InvalidSizeFieldAlignment {
+                context: "base block",
                 offset: self.offset_of_field(&self.base_block.data_size),
                 size: data_size,
                 expected_alignment,
             });
         }
 
-        // Does the size go beyond our hive data?
-        if data_size > self.data.len() {
-            return Err(NtHiveError::InvalidSizeField {
-                offset: self.offset_of_field(&self.base_block.data_size),
-                expected: data_size,
-                actual: self.data.len(),
-            });
-        }
-
         Ok(())
     }
 
@@ -307,9 +1099,9 @@ where
 
     fn validate_file_type(&self) -> Result<()> {
         let file_type = self.base_block.file_type.get();
-        let expected_file_type = HiveFileTypes::Primary as u32;
+        let expected_file_type = HiveFileType::Primary as u32;
 
-        if file_type == expected_file_type {
+        if file_type == expected_file_type || self.is_snapshot() {
             Ok(())
         } else {
             Err(NtHiveError::UnsupportedFileType {
@@ -320,6 +1112,11 @@ where
     }
 
     fn validate_sequence_numbers(&self) -> Result<()> {
+        // Snapshots have no logs to recover from, so their sequence numbers are irrelevant.
+        if self.is_snapshot() {
+            return Ok(());
+        }
+
         let primary_sequence_number = self.base_block.primary_sequence_number.get();
         let secondary_sequence_number = self.base_block.secondary_sequence_number.get();
 
@@ -341,6 +1138,7 @@ where
             Ok(())
         } else {
             Err(NtHiveError::InvalidFourByteSignature {
+                context: "base block",
                 offset: self.offset_of_field(signature),
                 expected: expected_signature,
                 actual: *signature,
@@ -373,7 +1171,7 @@ where
         root_key_node.clear_volatile_subkeys()
     }
 
-    pub(crate) fn root_key_node_mut(&mut self) -> Result<KeyNodeMut<B>> {
+    pub(crate) fn root_key_node_mut(&mut self) -> Result<KeyNodeMut<'_, B>> {
         let root_cell_offset = self.base_block.root_cell_offset.get();
         let cell_range = self.cell_range_from_data_offset(root_cell_offset)?;
         KeyNodeMut::from_cell_range(self, cell_range)
@@ -382,8 +1180,60 @@ where
 
 #[cfg(test)]
 mod tests {
+    use memoffset::offset_of;
+
     use crate::*;
 
+    #[test]
+    fn test_base_block() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.base_block_bytes().len(), 4096);
+        assert_eq!(&hive.base_block_bytes()[..4], b"regf");
+
+        let base_block = hive.base_block();
+        assert_eq!(&base_block.signature(), b"regf");
+        assert_eq!(base_block.major_version(), 1);
+        assert_eq!(
+            base_block.minor_version(),
+            HiveMinorVersion::WindowsXP as u32
+        );
+        assert_eq!(
+            base_block.primary_sequence_number(),
+            base_block.secondary_sequence_number()
+        );
+        assert_eq!(base_block.file_type(), 0);
+        assert_eq!(base_block.file_format(), 1);
+        assert_eq!(base_block.root_cell_offset(), 32);
+        assert_eq!(base_block.data_size(), 155648);
+        assert_eq!(base_block.clustering_factor(), 1);
+        assert!(base_block.file_name().is_empty());
+    }
+
+    #[test]
+    fn test_base_block_info() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let info = hive.base_block_info();
+        assert_eq!(info.signature, "regf");
+        assert_eq!(info.major_version, 1);
+        assert_eq!(info.minor_version, HiveMinorVersion::WindowsXP as u32);
+        assert_eq!(info.root_cell_offset, 32);
+        assert_eq!(info.data_size, 155648);
+        assert!(info.checksum_valid);
+
+        let output = info.to_string();
+        assert!(output.starts_with("Signature: regf\nVersion: 1.5\n"));
+        assert!(output.contains("Root cell offset: 0x00000020\n"));
+        assert!(output.ends_with("Checksum: valid"));
+
+        // A hive with a bad checksum can still be summarized.
+        let checksum_offset = offset_of!(BaseBlock, checksum);
+        testhive[checksum_offset] ^= 0xff;
+        let hive = Hive::without_validation(testhive.as_ref()).unwrap();
+        assert!(!hive.base_block_info().checksum_valid);
+    }
+
     #[test]
     fn test_clear_volatile_subkeys() {
         // clear_volatile_subkeys traverses all subkeys, so this test just checks
@@ -392,4 +1242,739 @@ mod tests {
         let mut hive = Hive::new(testhive.as_mut()).unwrap();
         assert!(hive.clear_volatile_subkeys().is_ok());
     }
+
+    #[test]
+    fn test_clustering_factor() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let clustering_factor_offset = offset_of!(BaseBlock, clustering_factor);
+
+        // Hives written to disks with 4K sectors have a clustering factor of 8.
+        testhive[clustering_factor_offset..clustering_factor_offset + 4]
+            .copy_from_slice(&8u32.to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.clustering_factor(), 8);
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.dword_data().unwrap(), 42);
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        assert!(key_value.data().unwrap().into_vec().is_ok());
+
+        // Implausible clustering factors are still rejected.
+        for clustering_factor in [0u32, 3, 256, 1 << 31] {
+            testhive[clustering_factor_offset..clustering_factor_offset + 4]
+                .copy_from_slice(&clustering_factor.to_le_bytes());
+            crate::helpers::tests::fix_checksum(&mut testhive);
+
+            assert_eq!(
+                Hive::new(testhive.as_ref()).err(),
+                Some(NtHiveError::UnsupportedClusteringFactor {
+                    max: 128,
+                    actual: clustering_factor
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_control_sets() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert!(hive.control_sets().unwrap().is_empty());
+
+        // Turn our testhive into a minimal SYSTEM hive with some control sets.
+        let system_hive = HivePatch::new()
+            .create_key("ControlSet003")
+            .create_key("ControlSet001\\Control")
+            .create_key("controlset002")
+            .create_key("ControlSetBackup")
+            .create_key("Select")
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(system_hive.as_ref()).unwrap();
+
+        let control_sets = hive.control_sets().unwrap();
+        let numbers = control_sets
+            .iter()
+            .map(|(number, _)| *number)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, [1, 2, 3]);
+
+        let (_, key_node) = &control_sets[0];
+        assert_eq!(key_node.name().unwrap(), "ControlSet001");
+        assert!(key_node.subkey("Control").is_some());
+    }
+
+    #[test]
+    fn test_current_control_set() {
+        // Turn our testhive into a minimal SYSTEM hive with two control sets.
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert!(hive.current_control_set().is_none());
+
+        let current = 2u32.to_le_bytes();
+        let system_hive = HivePatch::new()
+            .create_key("ControlSet001\\Control")
+            .create_key("ControlSet002\\Services")
+            .create_key("Select")
+            .set_value("Select", "Current", KeyValueDataType::RegDWord, &current)
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(system_hive.as_ref()).unwrap();
+
+        let key_node = hive.current_control_set().unwrap().unwrap();
+        assert_eq!(key_node.name().unwrap(), "ControlSet002");
+        assert!(key_node.subkey("Services").is_some());
+
+        // A reference to a missing control set must not resolve.
+        let current = 3u32.to_le_bytes();
+        let system_hive = HivePatch::new()
+            .set_value("Select", "Current", KeyValueDataType::RegDWord, &current)
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(system_hive.as_ref()).unwrap();
+        assert!(hive.current_control_set().is_none());
+    }
+
+    #[test]
+    fn test_data_type_counts() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let counts = hive.data_type_counts().unwrap();
+
+        let expected = [
+            (KeyValueDataType::RegSZ, 2),
+            (KeyValueDataType::RegExpandSZ, 1),
+            (KeyValueDataType::RegBinary, 4),
+            (KeyValueDataType::RegDWord, 1),
+            (KeyValueDataType::RegDWordBigEndian, 1),
+            (KeyValueDataType::RegMultiSZ, 2),
+            (KeyValueDataType::RegQWord, 1),
+        ]
+        .into_iter()
+        .map(|(data_type, count)| (data_type as u32, count))
+        .collect::<alloc::collections::BTreeMap<_, _>>();
+        assert_eq!(counts, expected);
+
+        // Unknown data type codes are counted as well.
+        let key_value = hive
+            .root_key_node()
+            .unwrap()
+            .subkey("data-test")
+            .unwrap()
+            .unwrap()
+            .value("binary")
+            .unwrap()
+            .unwrap();
+        let data_type_offset = 4096
+            + key_value.cell_offset() as usize
+            + 4
+            + offset_of!(crate::key_value::KeyValueHeader, data_type);
+
+        let mut testhive = testhive.clone();
+        testhive[data_type_offset..data_type_offset + 4].copy_from_slice(&0x1234u32.to_le_bytes());
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let counts = hive.data_type_counts().unwrap();
+        assert_eq!(counts[&(KeyValueDataType::RegBinary as u32)], 3);
+        assert_eq!(counts[&0x1234], 1);
+    }
+
+    #[test]
+    fn test_find_keys() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // Find all keys that have at least one value.
+        let paths = hive
+            .find_keys(|key_node| key_node.has_values())
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["big-data-test", "data-test"]);
+
+        // Predicates may combine several criteria.
+        let mut keys = hive.find_keys(|key_node| {
+            key_node.subkey("subkey").is_some() && key_node.value("anything").is_none()
+        });
+        let (path, key_node) = keys.next().unwrap().unwrap();
+        assert_eq!(path, "subpath-test\\with-single-level-subkey");
+        assert_eq!(key_node.name().unwrap(), "with-single-level-subkey");
+        assert!(keys.next().is_none());
+    }
+
+    #[test]
+    fn test_find_keys_named() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .create_key("data-test\\Subkey")
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+
+        // Keys are found at any depth and regardless of the case of their names.
+        let found = hive
+            .find_keys_named_with_paths("SUBKEY")
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                "data-test\\Subkey",
+                "subpath-test\\with-single-level-subkey\\subkey"
+            ]
+        );
+
+        let found = hive
+            .find_keys_named("subkey2")
+            .map(|item| item.unwrap().name().unwrap().to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["subkey2"]);
+
+        // The root key itself has an empty path.
+        let found = hive
+            .find_keys_named_with_paths("root")
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(found, [""]);
+
+        assert_eq!(hive.find_keys_named("nonexistent").count(), 0);
+    }
+
+    #[test]
+    fn test_for_each_key_with_progress() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_count = 1 + root_key_node.descendants(TraversalMode::Strict).count();
+        let value_count = root_key_node.all_values(TraversalMode::Strict).count();
+
+        for every in [1, 7, 100, key_count + 1] {
+            let mut reports = Vec::new();
+            let mut visited = 0;
+            hive.for_each_key_with_progress(
+                every,
+                |keys, values| reports.push((keys, values)),
+                |_| {
+                    visited += 1;
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+            assert_eq!(visited, key_count);
+            assert_eq!(reports.len(), key_count / every);
+            assert!(reports
+                .iter()
+                .enumerate()
+                .all(|(i, (keys, values))| *keys == (i + 1) * every && *values <= value_count));
+        }
+
+        // The last report of a per-key progress has the final counts.
+        let mut last_report = None;
+        hive.for_each_key_with_progress(
+            1,
+            |keys, values| last_report = Some((keys, values)),
+            |_| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(last_report, Some((key_count, value_count)));
+
+        // A zero interval never reports progress.
+        let mut traversal = hive.keys_with_progress(0, |_, _| panic!()).unwrap();
+        assert_eq!(traversal.by_ref().count(), key_count);
+        assert_eq!(traversal.keys_visited(), key_count);
+        assert_eq!(traversal.values_visited(), value_count);
+
+        // Errors returned by the callback end the traversal.
+        let mut visited = 0;
+        let error = NtHiveError::KeyNotFound {
+            path: "stop".into(),
+        };
+        let result = hive.for_each_key_with_progress(
+            1,
+            |_, _| (),
+            |_| {
+                visited += 1;
+                if visited == 3 {
+                    Err(error.clone())
+                } else {
+                    Ok(())
+                }
+            },
+        );
+        assert_eq!(result, Err(error));
+        assert_eq!(visited, 3);
+
+        // Errors from counting the values of a key end the iteration as well.
+        let mut testhive = testhive.clone();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_values_count_offset = 4096
+            + key_node.offset() as usize
+            + 4
+            + offset_of!(crate::key_node::KeyNodeHeader, key_values_count);
+        testhive[key_values_count_offset..key_values_count_offset + 4]
+            .copy_from_slice(&0x1000_0000u32.to_le_bytes());
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let mut traversal = hive.keys_with_progress(1, |_, _| ()).unwrap();
+        assert!(traversal.by_ref().any(|key_node| key_node.is_err()));
+        assert!(traversal.next().is_none());
+    }
+
+    #[test]
+    fn test_free_and_used_space() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let free_space = hive.free_space().unwrap();
+        let used_space = hive.used_space().unwrap();
+        assert!(free_space > 0);
+        assert!(used_space > 0);
+
+        // Every Hive Bin starts with a 32-byte header, the rest is filled with cells.
+        let data_size = hive.base_block().data_size() as usize;
+        let hive_bin_count = (0..data_size)
+            .step_by(4096)
+            .filter(|&offset| hive.slice(offset, 4).unwrap() == b"hbin")
+            .count();
+        assert_eq!(free_space + used_space, data_size - hive_bin_count * 32);
+
+        // Adding a value takes up free space.
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "new-value",
+                KeyValueDataType::RegBinary,
+                &[1; 100],
+            )
+            .apply(&hive)
+            .unwrap();
+        let patched_hive = Hive::new(patched.as_ref()).unwrap();
+        assert!(patched_hive.used_space().unwrap() > used_space);
+    }
+
+    #[test]
+    fn test_guids() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.minor_version(), HiveMinorVersion::WindowsXP as u32);
+
+        let guid_fields = [
+            offset_of!(BaseBlock, rm_id),
+            offset_of!(BaseBlock, log_id),
+            offset_of!(BaseBlock, tm_id),
+            offset_of!(BaseBlock, thaw_tm_id),
+        ];
+        for (i, offset) in guid_fields.into_iter().enumerate() {
+            let guid = core::array::from_fn::<u8, 16, _>(|j| (i * 16 + j) as u8);
+            testhive[offset..offset + 16].copy_from_slice(&guid);
+        }
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let expected_guid = |i: usize| Some(core::array::from_fn(|j| (i * 16 + j) as u8));
+        assert_eq!(hive.rm_id(), expected_guid(0));
+        assert_eq!(hive.log_id(), expected_guid(1));
+        assert_eq!(hive.tm_id(), expected_guid(2));
+        assert_eq!(hive.thaw_tm_id(), expected_guid(3));
+
+        // Hives before version 1.5 don't have these fields.
+        let minor_version_offset = offset_of!(BaseBlock, minor_version);
+        testhive[minor_version_offset..minor_version_offset + 4]
+            .copy_from_slice(&(HiveMinorVersion::WindowsNT4 as u32).to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.rm_id(), None);
+        assert_eq!(hive.log_id(), None);
+        assert_eq!(hive.tm_id(), None);
+        assert_eq!(hive.thaw_tm_id(), None);
+    }
+
+    #[test]
+    fn test_hive_too_small() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let data_size_offset = offset_of!(BaseBlock, data_size);
+
+        // Declare an almost 4 GiB hive data area over a tiny buffer.
+        let mut tiny = testhive[..4096 + 4096].to_vec();
+        tiny[data_size_offset..data_size_offset + 4].copy_from_slice(&0xffff_f000u32.to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut tiny);
+
+        assert_eq!(
+            Hive::new(tiny.as_ref()).err(),
+            Some(NtHiveError::HiveTooSmall {
+                declared: 0xffff_f000,
+                actual: 4096
+            })
+        );
+
+        // The same applies to a regular hive truncated after the base block.
+        let truncated = &testhive[..4096];
+        assert_eq!(
+            Hive::new(truncated).err(),
+            Some(NtHiveError::HiveTooSmall {
+                declared: 155648,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_keys_breadth_first() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let key_names = hive
+            .keys_breadth_first()
+            .map(|key_node| key_node.unwrap().name().unwrap().to_string_lossy())
+            .take(8)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            key_names,
+            [
+                "ROOT",
+                "big-data-test",
+                "character-encoding-test",
+                "data-test",
+                "subkey-test",
+                "subpath-test",
+                "äöü",
+                "𐐐"
+            ]
+        );
+
+        // Every key is visited exactly once.
+        fn count_keys(key_node: &KeyNode<&[u8]>) -> usize {
+            let subkeys = match key_node.subkeys() {
+                Some(subkeys) => subkeys.unwrap(),
+                None => return 1,
+            };
+
+            1 + subkeys
+                .map(|subkey| count_keys(&subkey.unwrap()))
+                .sum::<usize>()
+        }
+
+        let root_key_node = hive.root_key_node().unwrap();
+        assert_eq!(
+            hive.keys_breadth_first().count(),
+            count_keys(&root_key_node)
+        );
+    }
+
+    #[test]
+    fn test_last_reorganized() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.minor_version(), HiveMinorVersion::WindowsXP as u32);
+        assert_eq!(hive.last_reorganized(), None);
+
+        let minor_version_offset = offset_of!(BaseBlock, minor_version);
+        testhive[minor_version_offset..minor_version_offset + 4]
+            .copy_from_slice(&(HiveMinorVersion::WindowsVista as u32).to_le_bytes());
+        let timestamp_offset = offset_of!(BaseBlock, last_reorganized_timestamp);
+        testhive[timestamp_offset..timestamp_offset + 8]
+            .copy_from_slice(&0x01d9_1234_5678_9abcu64.to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.last_reorganized(), Some(0x01d9_1234_5678_9abc));
+    }
+
+    #[test]
+    fn test_new_at_offset() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let mut embedded = vec![0u8; 1024];
+        embedded.extend_from_slice(&testhive);
+
+        assert_eq!(Hive::find_signature(&testhive), Some(0));
+        assert_eq!(Hive::find_signature(&embedded), Some(1024));
+        assert_eq!(Hive::find_signature(&embedded[..1024]), None);
+
+        let hive = Hive::new_at_offset(embedded.as_ref(), 1024).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        assert_eq!(root_key_node.name().unwrap(), "ROOT");
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.dword_data().unwrap(), 42);
+
+        // Error offsets are relative to the beginning of the embedding bytes.
+        let data_size_offset = 1024 + offset_of!(BaseBlock, data_size);
+        embedded[data_size_offset..data_size_offset + 4].copy_from_slice(&1u32.to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut embedded[1024..]);
+        assert_eq!(
+            Hive::new_at_offset(embedded.as_ref(), 1024).err(),
+            Some(NtHiveError::InvalidSizeFieldAlignment {
+                context: "base block",
+                offset: data_size_offset,
+                size: 1,
+                expected_alignment: 4096
+            })
+        );
+
+        // Offsets beyond the end of the bytes are rejected.
+        assert_eq!(
+            Hive::new_at_offset(embedded.as_ref(), embedded.len() + 1).err(),
+            Some(NtHiveError::InvalidHeaderSize {
+                context: "base block",
+                offset: embedded.len() + 1,
+                expected: 4096,
+                actual: 0
+            })
+        );
+        assert!(matches!(
+            Hive::new_at_offset(embedded.as_ref(), embedded.len() - 100),
+            Err(NtHiveError::InvalidHeaderSize { actual: 100, .. })
+        ));
+    }
+
+    #[test]
+    fn test_security_descriptors() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // All keys of the testhive share a single Key Security cell.
+        let security_offsets = hive
+            .keys_breadth_first()
+            .filter_map(|key_node| key_node.unwrap().security_offset())
+            .collect::<alloc::collections::BTreeSet<_>>();
+        assert_eq!(security_offsets.len(), 1);
+
+        let security_descriptors = hive
+            .security_descriptors()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(security_descriptors.len(), 1);
+        let root_security_descriptor = hive.root_key_node().unwrap().security_descriptor();
+        assert_eq!(
+            security_descriptors[0],
+            root_security_descriptor.unwrap().unwrap()
+        );
+
+        // Add a second Key Security cell disguised as value data.
+        let mut fake_cell = Vec::new();
+        fake_cell.extend_from_slice(b"sk\0\0");
+        fake_cell.extend_from_slice(&[0; 12]);
+        fake_cell.extend_from_slice(&4u32.to_le_bytes());
+        fake_cell.extend_from_slice(&[1, 2, 3, 4]);
+        let mut testhive = HivePatch::new()
+            .set_value("", "fake-sk", KeyValueDataType::RegBinary, &fake_cell)
+            .apply(&hive)
+            .unwrap();
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let root_sk_offset = root_key_node.security_offset().unwrap() as usize;
+        let key_value = root_key_node.value("fake-sk").unwrap().unwrap();
+        let KeyValueData::Small(data) = key_value.data().unwrap() else {
+            panic!("fake-sk should be small");
+        };
+        let fake_sk_offset = hive.offset_of_field(data) - 4096 - core::mem::size_of::<CellHeader>();
+
+        let flink_offset = 4 + offset_of!(crate::key_security::KeySecurityHeader, flink);
+        let blink_offset = 4 + offset_of!(crate::key_security::KeySecurityHeader, blink);
+        let mut write_u32 = |data_offset: usize, value: usize| {
+            let offset = 4096 + data_offset;
+            testhive[offset..offset + 4].copy_from_slice(&(value as u32).to_le_bytes());
+        };
+
+        // Insert the fake cell into the circular list.
+        write_u32(root_sk_offset + flink_offset, fake_sk_offset);
+        write_u32(root_sk_offset + blink_offset, fake_sk_offset);
+        write_u32(fake_sk_offset + flink_offset, root_sk_offset);
+        write_u32(fake_sk_offset + blink_offset, root_sk_offset);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let security_descriptors = hive
+            .security_descriptors()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(security_descriptors.len(), 2);
+        assert_eq!(security_descriptors[1], [1, 2, 3, 4]);
+
+        // Let the fake cell loop to itself instead of returning to the first cell.
+        let fake_flink = 4096 + fake_sk_offset + flink_offset;
+        testhive[fake_flink..fake_flink + 4]
+            .copy_from_slice(&(fake_sk_offset as u32).to_le_bytes());
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let mut iter = hive.security_descriptors().unwrap();
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(iter.next().unwrap().unwrap(), [1, 2, 3, 4]);
+        assert_eq!(
+            iter.next().unwrap(),
+            Err(NtHiveError::InvalidKeySecurityBlink {
+                offset: 4096 + fake_sk_offset + blink_offset,
+                expected: fake_sk_offset as u32,
+                actual: root_sk_offset as u32,
+            })
+        );
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_slice() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // The first hive bin begins with the "hbin" signature.
+        assert_eq!(hive.slice(0, 4).unwrap(), b"hbin");
+        assert_eq!(hive.slice(0, 0).unwrap(), b"");
+
+        let root_cell_offset = hive.root_key_node().unwrap().offset() as usize;
+        assert_eq!(hive.slice(root_cell_offset + 4, 2).unwrap(), b"nk");
+
+        let data_len = testhive.len() - 4096;
+        assert_eq!(hive.slice(data_len - 8, 8).unwrap().len(), 8);
+        assert_eq!(
+            hive.slice(data_len - 8, 9),
+            Err(NtHiveError::InvalidDataSize {
+                context: "slice",
+                offset: 4096 + data_len - 8,
+                expected: 9,
+                actual: 8
+            })
+        );
+        assert!(hive.slice(data_len + 1, 0).is_err());
+        assert!(hive.slice(usize::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.file_type(), Some(HiveFileType::Primary));
+        assert!(!hive.is_snapshot());
+
+        // Turn our testhive into a `RegSaveKey`-style hive without matching sequence numbers.
+        testhive[4..8].copy_from_slice(&2u32.to_le_bytes());
+        testhive[8..12].copy_from_slice(&1u32.to_le_bytes());
+        testhive[28..32].copy_from_slice(&(HiveFileType::External as u32).to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.file_type(), Some(HiveFileType::External));
+        assert!(hive.is_snapshot());
+
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert_eq!(key_node.subkeys().unwrap().unwrap().count(), 512);
+
+        // Log files are still rejected.
+        testhive[28..32].copy_from_slice(&(HiveFileType::Log as u32).to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+        assert!(matches!(
+            Hive::new(testhive.as_ref()),
+            Err(NtHiveError::SequenceNumberMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_supports() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert!(hive.supports(HiveFeature::BigData));
+        assert!(hive.supports(HiveFeature::HashLeafs));
+        assert!(!hive.supports(HiveFeature::ReorgStats));
+        assert!(hive.supports(HiveFeature::TransactionIds));
+
+        // A version 1.3 hive has none of these features.
+        let minor_version_offset = offset_of!(BaseBlock, minor_version);
+        testhive[minor_version_offset..minor_version_offset + 4]
+            .copy_from_slice(&(HiveMinorVersion::WindowsNT4 as u32).to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert!(!hive.supports(HiveFeature::BigData));
+        assert!(!hive.supports(HiveFeature::HashLeafs));
+        assert!(!hive.supports(HiveFeature::ReorgStats));
+        assert!(!hive.supports(HiveFeature::TransactionIds));
+        assert_eq!(hive.rm_id(), None);
+        assert_eq!(hive.last_reorganized(), None);
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.declared_len(), testhive.len());
+        assert_eq!(hive.buffer_len(), testhive.len());
+
+        // Append 4 KiB of garbage resembling an allocated cell.
+        let mut with_garbage = testhive.clone();
+        with_garbage.extend_from_slice(&(-4096i32).to_le_bytes());
+        with_garbage.extend_from_slice(b"nk");
+        with_garbage.resize(testhive.len() + 4096, 0xaa);
+
+        let hive_with_garbage = Hive::new(with_garbage.as_ref()).unwrap();
+        assert_eq!(hive_with_garbage.declared_len(), testhive.len());
+        assert_eq!(hive_with_garbage.buffer_len(), testhive.len() + 4096);
+
+        // Enumeration is unaffected.
+        let key_names = |hive: &Hive<&[u8]>| {
+            hive.keys_breadth_first()
+                .map(|key_node| key_node.unwrap().name().unwrap().to_string_lossy())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(key_names(&hive_with_garbage), key_names(&hive));
+        assert_eq!(
+            hive_with_garbage.hive_bins().count(),
+            hive.hive_bins().count()
+        );
+
+        // Offsets into the trailing bytes are not resolved.
+        let data_size = hive.base_block().data_size();
+        assert!(hive_with_garbage.key_at(data_size).is_err());
+        assert!(hive_with_garbage.slice(data_size as usize, 1).is_err());
+
+        // Without validation, the declared data size can't be trusted and everything is kept.
+        let unvalidated = Hive::without_validation(with_garbage.as_ref()).unwrap();
+        assert_eq!(unvalidated.buffer_len(), testhive.len() + 4096);
+        assert!(unvalidated.slice(data_size as usize, 1).is_ok());
+    }
+
+    #[test]
+    fn test_validate_value_cells() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(hive.validate_value_cells(), Ok(()));
+
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let mut key_values = key_node.values().unwrap().unwrap();
+        let shared_offset = key_values.next().unwrap().unwrap().cell_offset();
+
+        let mut patched = HivePatch::new()
+            .create_key("shared")
+            .set_value("shared", "value", KeyValueDataType::RegDWord, &[1, 0, 0, 0])
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        assert_eq!(hive.validate_value_cells(), Ok(()));
+
+        // Make the values list of the new key reference a value of `data-test`.
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("shared").unwrap().unwrap();
+        let list_offset_field = 4096
+            + key_node.offset() as usize
+            + 4
+            + offset_of!(crate::key_node::KeyNodeHeader, key_values_list_offset);
+        let list_offset = u32::from_le_bytes(
+            patched[list_offset_field..list_offset_field + 4]
+                .try_into()
+                .unwrap(),
+        );
+        let list_item = 4096 + list_offset as usize + 4;
+        patched[list_item..list_item + 4].copy_from_slice(&shared_offset.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        assert_eq!(
+            hive.validate_value_cells(),
+            Err(NtHiveError::SharedValueCell {
+                offset: 4096 + shared_offset as usize
+            })
+        );
+    }
 }
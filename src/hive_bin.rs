@@ -0,0 +1,417 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::Range;
+
+use zerocopy::byteorder::LittleEndian;
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout, Ref, SplitByteSlice, Unaligned, U32, U64,
+};
+
+use crate::error::{NtHiveError, Result};
+use crate::helpers::byte_subrange;
+use crate::hive::{CellHeader, Hive};
+
+/// Every Hive Bin has a size that is a multiple of this value.
+pub(crate) const HIVE_BIN_ALIGNMENT: usize = 4096;
+
+/// Every cell has a size that is a multiple of this value.
+pub(crate) const CELL_ALIGNMENT: usize = 8;
+
+/// On-Disk Structure of a Hive Bin header.
+#[allow(dead_code)]
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+pub(crate) struct HiveBinHeader {
+    pub(crate) signature: [u8; 4],
+    pub(crate) offset: U32<LittleEndian>,
+    pub(crate) size: U32<LittleEndian>,
+    pub(crate) reserved: [U32<LittleEndian>; 2],
+    pub(crate) timestamp: U64<LittleEndian>,
+    pub(crate) spare: U32<LittleEndian>,
+}
+
+/// A single cell found by [`HiveBinCells`].
+pub(crate) struct HiveBinCell {
+    /// Size of the cell including its header.
+    pub(crate) size: usize,
+    /// Whether this cell is allocated (has a negative size field).
+    pub(crate) allocated: bool,
+}
+
+/// Iterator over
+///   all Hive Bins of a hive and the cells in them,
+///   returning a [`HiveBinCell`] for each cell.
+///
+/// The cells of a Hive Bin must exactly fill it.
+/// A cell exceeding its Hive Bin, and therefore overlapping the next one, is reported as
+/// [`NtHiveError::OverlappingCells`].
+///
+/// On-Disk Signature: `hbin`
+pub(crate) struct HiveBinCells<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    cells_range: Range<usize>,
+    next_hive_bin_offset: usize,
+    data_size: usize,
+    finished: bool,
+}
+
+impl<'h, B> HiveBinCells<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(hive: &'h Hive<B>) -> Self {
+        // `Hive::validate_data_size` usually ensures this, but `Hive::without_validation` skips it.
+        let data_size = hive.base_block.data_size.get() as usize;
+
+        Self {
+            hive,
+            cells_range: 0..0,
+            next_hive_bin_offset: 0,
+            data_size: data_size.min(hive.data.len()),
+            finished: false,
+        }
+    }
+
+    fn next_cell(&mut self) -> Option<Result<HiveBinCell>> {
+        while self.cells_range.is_empty() {
+            if self.next_hive_bin_offset >= self.data_size {
+                return None;
+            }
+
+            self.cells_range = iter_try!(self.read_hive_bin_header());
+        }
+
+        // Cell sizes are aligned, so the Hive Bin has room for at least a cell header.
+        let cell_offset = self.cells_range.start;
+        let header_range = byte_subrange(&self.cells_range, mem::size_of::<CellHeader>()).unwrap();
+        let header = Ref::<&[u8], CellHeader>::from_bytes(&self.hive.data[header_range]).unwrap();
+        let size_field = header.size.get();
+        let cell_size = size_field.unsigned_abs() as usize;
+
+        if cell_size % CELL_ALIGNMENT != 0 {
+            return Some(Err(NtHiveError::InvalidSizeFieldAlignment {
+                context: "cell",
+                offset: self.hive.offset_of_field(&header.size),
+                size: cell_size,
+                expected_alignment: CELL_ALIGNMENT,
+            }));
+        }
+
+        // An empty cell would be overlapped by the next one.
+        if cell_size == 0 || cell_size > self.cells_range.len() {
+            return Some(Err(NtHiveError::OverlappingCells {
+                offset: self.hive.offset_of_data_offset(cell_offset),
+            }));
+        }
+
+        self.cells_range.start += cell_size;
+
+        Some(Ok(HiveBinCell {
+            size: cell_size,
+            allocated: size_field < 0,
+        }))
+    }
+
+    /// Validates the Hive Bin header at `next_hive_bin_offset` and returns the range of its cells.
+    fn read_hive_bin_header(&mut self) -> Result<Range<usize>> {
+        let hive_bin_range = hive_bin_range(self.hive, self.next_hive_bin_offset, self.data_size)?;
+        self.next_hive_bin_offset = hive_bin_range.end;
+        Ok(hive_bin_range.start + mem::size_of::<HiveBinHeader>()..hive_bin_range.end)
+    }
+}
+
+impl<B> Iterator for HiveBinCells<'_, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<HiveBinCell>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_cell();
+
+        // We cannot find the next cell after an error.
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        item
+    }
+}
+
+impl<B> FusedIterator for HiveBinCells<'_, B> where B: SplitByteSlice {}
+
+/// A single Hive Bin, returned by [`HiveBins`].
+///
+/// Hive Bins are the units in which a hive grows.
+/// Their headers record where and when they have been allocated, which can hint at the order
+/// in which parts of a hive have been written.
+///
+/// On-Disk Signature: `hbin`
+pub struct HiveBin<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    header_range: Range<usize>,
+}
+
+impl<B> HiveBin<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn header(&self) -> Ref<&[u8], HiveBinHeader> {
+        Ref::from_bytes(&self.hive.data[self.header_range.clone()]).unwrap()
+    }
+
+    /// Returns the data offset of this Hive Bin, as recorded in its header.
+    ///
+    /// This is relative to the first Hive Bin, so the first Hive Bin always records 0.
+    pub fn offset_in_file(&self) -> u32 {
+        self.header().offset.get()
+    }
+
+    /// Returns the size of this Hive Bin in bytes, including its header.
+    pub fn size(&self) -> u32 {
+        self.header().size.get()
+    }
+
+    /// Returns the timestamp of this Hive Bin in the FILETIME format.
+    ///
+    /// Windows only sets it for the first Hive Bin of a hive and after reorganizations,
+    /// so it is frequently zero.
+    pub fn timestamp(&self) -> u64 {
+        self.header().timestamp.get()
+    }
+}
+
+/// Iterator over
+///   all Hive Bins of a hive,
+///   returning a [`HiveBin`] for each Hive Bin.
+///
+/// Returned by [`Hive::hive_bins`].
+/// The iteration stops after the first error, as the next Hive Bin cannot be found then.
+///
+/// On-Disk Signature: `hbin`
+#[derive(Clone)]
+pub struct HiveBins<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    next_hive_bin_offset: usize,
+    data_size: usize,
+    finished: bool,
+}
+
+impl<'h, B> HiveBins<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(hive: &'h Hive<B>) -> Self {
+        // `Hive::validate_data_size` usually ensures this, but `Hive::without_validation` skips it.
+        let data_size = hive.base_block.data_size.get() as usize;
+
+        Self {
+            hive,
+            next_hive_bin_offset: 0,
+            data_size: data_size.min(hive.data.len()),
+            finished: false,
+        }
+    }
+}
+
+impl<'h, B> Iterator for HiveBins<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<HiveBin<'h, B>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.next_hive_bin_offset >= self.data_size {
+            return None;
+        }
+
+        match hive_bin_range(self.hive, self.next_hive_bin_offset, self.data_size) {
+            Ok(hive_bin_range) => {
+                self.next_hive_bin_offset = hive_bin_range.end;
+                let header_start = hive_bin_range.start;
+                let header_end = header_start + mem::size_of::<HiveBinHeader>();
+
+                Some(Ok(HiveBin {
+                    hive: self.hive,
+                    header_range: header_start..header_end,
+                }))
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<B> FusedIterator for HiveBins<'_, B> where B: SplitByteSlice {}
+
+/// Validates the Hive Bin header at `hive_bin_offset` and returns the range of the entire Hive Bin.
+fn hive_bin_range<B>(
+    hive: &Hive<B>,
+    hive_bin_offset: usize,
+    data_size: usize,
+) -> Result<Range<usize>>
+where
+    B: SplitByteSlice,
+{
+    let remaining_range = hive_bin_offset..data_size;
+    let header_range = byte_subrange(&remaining_range, mem::size_of::<HiveBinHeader>())
+        .ok_or_else(|| NtHiveError::InvalidHeaderSize {
+            context: "hive bin",
+            offset: hive.offset_of_data_offset(hive_bin_offset),
+            expected: mem::size_of::<HiveBinHeader>(),
+            actual: remaining_range.len(),
+        })?;
+    let header = Ref::<&[u8], HiveBinHeader>::from_bytes(&hive.data[header_range]).unwrap();
+
+    if &header.signature != b"hbin" {
+        return Err(NtHiveError::InvalidFourByteSignature {
+            context: "hive bin",
+            offset: hive.offset_of_field(&header.signature),
+            expected: b"hbin",
+            actual: header.signature,
+        });
+    }
+
+    let hive_bin_size = header.size.get() as usize;
+    if hive_bin_size == 0 || hive_bin_size % HIVE_BIN_ALIGNMENT != 0 {
+        return Err(NtHiveError::InvalidSizeFieldAlignment {
+            context: "hive bin",
+            offset: hive.offset_of_field(&header.size),
+            size: hive_bin_size,
+            expected_alignment: HIVE_BIN_ALIGNMENT,
+        });
+    }
+
+    byte_subrange(&remaining_range, hive_bin_size).ok_or_else(|| NtHiveError::InvalidSizeField {
+        context: "hive bin",
+        offset: hive.offset_of_field(&header.size),
+        expected: hive_bin_size,
+        actual: remaining_range.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use memoffset::offset_of;
+
+    use super::{HiveBinCells, HiveBinHeader};
+    use crate::*;
+
+    #[test]
+    fn test_hive_bin_cells() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // The cells must exactly fill the hive data, minus the Hive Bin headers.
+        let hive_bin_count = hive.hive_bins().count();
+        let cells_size = HiveBinCells::new(&hive)
+            .map(|cell| cell.unwrap().size)
+            .sum::<usize>();
+        let data_size = hive.base_block().data_size() as usize;
+        assert!(hive_bin_count >= 1);
+        assert_eq!(
+            cells_size,
+            data_size - hive_bin_count * core::mem::size_of::<HiveBinHeader>()
+        );
+
+        // The cells must be returned in order and each Hive Bin must be filled by its own cells.
+        // The root key must be one of the allocated cells.
+        let root_cell_offset = hive.root_key_node().unwrap().offset() as usize;
+        let mut cells = HiveBinCells::new(&hive).map(|cell| cell.unwrap());
+        let mut root_cell_allocated = None;
+
+        for hive_bin in hive.hive_bins() {
+            let hive_bin = hive_bin.unwrap();
+            let hive_bin_end = hive_bin.offset_in_file() as usize + hive_bin.size() as usize;
+            let mut offset =
+                hive_bin.offset_in_file() as usize + core::mem::size_of::<HiveBinHeader>();
+
+            while offset < hive_bin_end {
+                let cell = cells.next().unwrap();
+                if offset == root_cell_offset {
+                    root_cell_allocated = Some(cell.allocated);
+                }
+                offset += cell.size;
+            }
+
+            assert_eq!(offset, hive_bin_end);
+        }
+
+        assert!(cells.next().is_none());
+        assert_eq!(root_cell_allocated, Some(true));
+    }
+
+    #[test]
+    fn test_hive_bins() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // The Hive Bins must exactly fill the hive data and record their own offsets.
+        let mut offset = 0;
+        for hive_bin in hive.hive_bins() {
+            let hive_bin = hive_bin.unwrap();
+            assert_eq!(hive_bin.offset_in_file() as usize, offset);
+            offset += hive_bin.size() as usize;
+        }
+
+        assert_eq!(offset, hive.base_block().data_size() as usize);
+
+        let first_hive_bin = hive.hive_bins().next().unwrap().unwrap();
+        assert_eq!(first_hive_bin.offset_in_file(), 0);
+        let timestamp_offset = 4096 + offset_of!(HiveBinHeader, timestamp);
+        assert_eq!(
+            first_hive_bin.timestamp(),
+            u64::from_le_bytes(
+                testhive[timestamp_offset..timestamp_offset + 8]
+                    .try_into()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_overlapping_cells() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_cell_offset = hive.root_key_node().unwrap().offset() as usize;
+        let hive_bin_size_offset = 4096 + offset_of!(HiveBinHeader, size);
+        let hive_bin_size = u32::from_le_bytes(
+            testhive[hive_bin_size_offset..hive_bin_size_offset + 4]
+                .try_into()
+                .unwrap(),
+        ) as i32;
+
+        // Let the root key cell extend beyond the end of the first Hive Bin.
+        let cell_size_offset = 4096 + root_cell_offset;
+        testhive[cell_size_offset..cell_size_offset + 4]
+            .copy_from_slice(&(-hive_bin_size).to_le_bytes());
+
+        // This is only detected on request.
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(
+            hive.validate_cells(),
+            Err(NtHiveError::OverlappingCells {
+                offset: cell_size_offset
+            })
+        );
+
+        // An empty cell is overlapped by the following one.
+        testhive[cell_size_offset..cell_size_offset + 4].copy_from_slice(&0i32.to_le_bytes());
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        assert_eq!(
+            hive.validate_cells(),
+            Err(NtHiveError::OverlappingCells {
+                offset: cell_size_offset
+            })
+        );
+    }
+}
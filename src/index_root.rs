@@ -19,7 +19,7 @@ use crate::leaf::LeafItemRanges;
 
 /// On-Disk Structure of a single Index Root item.
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct IndexRootItem {
     subkeys_list_offset: U32<LittleEndian>,
 }
@@ -56,11 +56,16 @@ pub(crate) struct IndexRootItemRanges {
 }
 
 impl IndexRootItemRanges {
-    fn new(count: u16, count_field_offset: usize, data_range: Range<usize>) -> Result<Self> {
+    pub(crate) fn new(
+        count: u16,
+        count_field_offset: usize,
+        data_range: Range<usize>,
+    ) -> Result<Self> {
         let byte_count = count as usize * mem::size_of::<IndexRootItem>();
 
         let items_range = byte_subrange(&data_range, byte_count).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "index root",
                 offset: count_field_offset,
                 expected: byte_count,
                 actual: data_range.len(),
@@ -179,6 +184,31 @@ where
             self.leaf_item_ranges = Some(leaf_item_ranges);
         }
     }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf_item_ranges) = self.leaf_item_ranges.as_mut() {
+                // Skip entire Leafs without looking at their Key Nodes.
+                let leaf_item_count = leaf_item_ranges.len();
+                if n < leaf_item_count {
+                    let leaf_item_range = leaf_item_ranges.nth(n).unwrap();
+                    let key_node =
+                        iter_try!(KeyNode::from_leaf_item_range(self.hive, leaf_item_range));
+                    return Some(Ok(key_node));
+                }
+
+                n -= leaf_item_count;
+                self.leaf_item_ranges = None;
+            }
+
+            let index_root_item_range = self.index_root_item_ranges.next()?;
+            let leaf_item_ranges = iter_try!(LeafItemRanges::from_index_root_item_range(
+                self.hive,
+                index_root_item_range
+            ));
+            self.leaf_item_ranges = Some(leaf_item_ranges);
+        }
+    }
 }
 
 impl<B> FusedIterator for IndexRootKeyNodes<'_, B> where B: SplitByteSlice {}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: GPL-2.0-or-later
 
 use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
 use core::mem;
 use core::ops::Range;
 use core::ptr;
@@ -15,16 +16,31 @@ use zerocopy::{
 
 use crate::error::{NtHiveError, Result};
 use crate::helpers::byte_subrange;
-use crate::hive::Hive;
+use crate::hive::{CellHeader, Hive};
 use crate::index_root::IndexRootItemRanges;
-use crate::key_value::KeyValue;
+use crate::key_value::{KeyValue, KeyValueEntry};
 use crate::key_values_list::KeyValues;
-use crate::leaf::{LeafItemRange, LeafItemRanges};
+use crate::leaf::{HashLeafEntries, LeafItemRange, LeafItemRanges};
 use crate::string::NtHiveNameString;
 use crate::subkeys_list::{SubKeyNodes, SubKeyNodesMut};
 
+#[cfg(feature = "alloc")]
+use {
+    crate::key_snapshot::KeySnapshot,
+    crate::string::OwnedNtHiveName,
+    crate::traversal::{AllValues, Descendants, SubtreeKeys, TraversalMode, VisitedKeyNodes},
+    alloc::collections::{BTreeMap, VecDeque},
+    alloc::{borrow::ToOwned, string::String, vec::Vec},
+    core::iter::{FusedIterator, Take},
+};
+
+/// Factor by which the subkey count of a Key Node may exceed the `max` argument of
+/// [`KeyNode::subkeys_limited`] in [`TraversalMode::Strict`].
+#[cfg(feature = "alloc")]
+pub const SUBKEYS_LIMITED_STRICT_FACTOR: usize = 16;
+
 bitflags! {
-    struct KeyNodeFlags: u16 {
+    pub(crate) struct KeyNodeFlags: u16 {
         /// This is a volatile key (not stored on disk).
         const KEY_IS_VOLATILE = 0x0001;
         /// This is the mount point of another hive (not stored on disk).
@@ -51,28 +67,28 @@ bitflags! {
 /// On-Disk Structure of a Key Node header.
 #[allow(dead_code)]
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
-struct KeyNodeHeader {
-    signature: [u8; 2],
-    flags: U16<LittleEndian>,
-    timestamp: U64<LittleEndian>,
-    spare: U32<LittleEndian>,
-    parent: U32<LittleEndian>,
-    subkey_count: U32<LittleEndian>,
-    volatile_subkey_count: U32<LittleEndian>,
-    subkeys_list_offset: U32<LittleEndian>,
-    volatile_subkeys_list_offset: U32<LittleEndian>,
-    key_values_count: U32<LittleEndian>,
-    key_values_list_offset: U32<LittleEndian>,
-    key_security_offset: U32<LittleEndian>,
-    class_name_offset: U32<LittleEndian>,
-    max_subkey_name: U32<LittleEndian>,
-    max_subkey_class_name: U32<LittleEndian>,
-    max_value_name: U32<LittleEndian>,
-    max_value_data: U32<LittleEndian>,
-    work_var: U32<LittleEndian>,
-    key_name_length: U16<LittleEndian>,
-    class_name_length: U16<LittleEndian>,
+#[repr(C, packed)]
+pub(crate) struct KeyNodeHeader {
+    pub(crate) signature: [u8; 2],
+    pub(crate) flags: U16<LittleEndian>,
+    pub(crate) timestamp: U64<LittleEndian>,
+    pub(crate) spare: U32<LittleEndian>,
+    pub(crate) parent: U32<LittleEndian>,
+    pub(crate) subkey_count: U32<LittleEndian>,
+    pub(crate) volatile_subkey_count: U32<LittleEndian>,
+    pub(crate) subkeys_list_offset: U32<LittleEndian>,
+    pub(crate) volatile_subkeys_list_offset: U32<LittleEndian>,
+    pub(crate) key_values_count: U32<LittleEndian>,
+    pub(crate) key_values_list_offset: U32<LittleEndian>,
+    pub(crate) key_security_offset: U32<LittleEndian>,
+    pub(crate) class_name_offset: U32<LittleEndian>,
+    pub(crate) max_subkey_name: U32<LittleEndian>,
+    pub(crate) max_subkey_class_name: U32<LittleEndian>,
+    pub(crate) max_value_name: U32<LittleEndian>,
+    pub(crate) max_value_data: U32<LittleEndian>,
+    pub(crate) work_var: U32<LittleEndian>,
+    pub(crate) key_name_length: U16<LittleEndian>,
+    pub(crate) class_name_length: U16<LittleEndian>,
 }
 
 /// Byte range of a single Key Node item.
@@ -90,6 +106,7 @@ impl KeyNodeItemRange {
         let header_range =
             byte_subrange(&cell_range, mem::size_of::<KeyNodeHeader>()).ok_or_else(|| {
                 NtHiveError::InvalidHeaderSize {
+                    context: "key node",
                     offset: hive.offset_of_data_offset(cell_range.start),
                     expected: mem::size_of::<KeyNodeHeader>(),
                     actual: cell_range.len(),
@@ -237,6 +254,7 @@ impl KeyNodeItemRange {
             class_name_length
         )
         .ok_or_else(|| NtHiveError::InvalidSizeField {
+            context: "key node",
             offset: hive.offset_of_field(&header.class_name_length),
             expected: class_name_length,
             actual: class_name_offset_range.len(),
@@ -270,6 +288,7 @@ impl KeyNodeItemRange {
 
         let key_name_range = byte_subrange(&self.data_range, key_name_length).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "key node",
                 offset: hive.offset_of_field(&header.key_name_length),
                 expected: key_name_length,
                 actual: self.data_range.len(),
@@ -304,6 +323,20 @@ impl KeyNodeItemRange {
     }
 
     fn subkeys_cell_range<B>(&self, hive: &Hive<B>) -> Option<Result<Range<usize>>>
+    where
+        B: SplitByteSlice,
+    {
+        if self.header(hive).subkey_count.get() == 0 {
+            // This Key Node has no subkeys.
+            // Don't even look at the list offset if there are no subkeys, it may be bogus.
+            return None;
+        }
+
+        self.subkeys_list_cell_range(hive)
+    }
+
+    /// Like [`KeyNodeItemRange::subkeys_cell_range`], but doesn't trust the subkey count.
+    fn subkeys_list_cell_range<B>(&self, hive: &Hive<B>) -> Option<Result<Range<usize>>>
     where
         B: SplitByteSlice,
     {
@@ -334,7 +367,7 @@ impl KeyNodeItemRange {
         Some(Ok(key_node_item_range))
     }
 
-    fn timestamp<'h, B>(&self, hive: &'h Hive<B>) -> Result<u64>
+    fn timestamp<B>(&self, hive: &Hive<B>) -> Result<u64>
     where
         B: SplitByteSlice,
     {
@@ -354,6 +387,7 @@ impl KeyNodeItemRange {
             Ok(())
         } else {
             Err(NtHiveError::InvalidTwoByteSignature {
+                context: "key node",
                 offset: hive.offset_of_field(signature),
                 expected: expected_signature,
                 actual: *signature,
@@ -388,13 +422,14 @@ impl KeyNodeItemRange {
     {
         let header = self.header(hive);
         let key_values_list_offset = header.key_values_list_offset.get();
-        if key_values_list_offset == u32::MAX {
+        let count = header.key_values_count.get();
+        if key_values_list_offset == u32::MAX || count == 0 {
             // This Key Node has no values.
+            // Don't even look at the list offset if there are no values, it may be bogus.
             return None;
         }
 
         let cell_range = iter_try!(hive.cell_range_from_data_offset(key_values_list_offset));
-        let count = header.key_values_count.get();
         let count_field_offset = hive.offset_of_field(&header.key_values_count);
 
         Some(KeyValues::new(hive, count, count_field_offset, cell_range))
@@ -407,7 +442,6 @@ impl KeyNodeItemRange {
 /// On-Disk Signature: `nk`
 ///
 /// [`KeyValue`]: crate::key_value::KeyValue
-#[derive(Clone)]
 pub struct KeyNode<'h, B: SplitByteSlice> {
     hive: &'h Hive<B>,
     item_range: KeyNodeItemRange,
@@ -430,16 +464,214 @@ where
         Ok(Self { hive, item_range })
     }
 
+    /// Returns an iterator over the values of this Key Node and all of its descendants.
+    ///
+    /// Keys are visited depth-first in hive order.
+    /// `mode` determines whether the iteration stops at the first error or skips erroneous
+    /// keys and values.
+    #[cfg(feature = "alloc")]
+    pub fn all_values(&self, mode: TraversalMode) -> AllValues<'h, B> {
+        AllValues::new(self.clone(), mode)
+    }
+
     /// Returns the class name of this Key Node (if any).
-    pub fn class_name(&self) -> Option<Result<NtHiveNameString>> {
+    pub fn class_name(&self) -> Option<Result<NtHiveNameString<'_>>> {
         self.item_range.class_name(self.hive)
     }
 
+    /// Returns an iterator over all descendants of this Key Node (excluding itself).
+    ///
+    /// Keys are visited depth-first in hive order.
+    /// `mode` determines whether the iteration stops at the first error or skips erroneous keys.
+    #[cfg(feature = "alloc")]
+    pub fn descendants(&self, mode: TraversalMode) -> Descendants<'h, B> {
+        Descendants::new(self.clone(), mode)
+    }
+
+    /// Returns an iterator over all values named `name` (compared case-insensitively) in this
+    /// Key Node and all of its descendants.
+    ///
+    /// Each value is returned along with the backslash-separated path of its Key Node,
+    /// relative to this Key Node.
+    /// The path is empty for values of this Key Node itself.
+    /// Keys are visited depth-first in hive order.
+    /// The iteration stops after the first error, as the remaining keys may be unreachable.
+    #[cfg(feature = "alloc")]
+    pub fn find_values_named(
+        &self,
+        name: &str,
+    ) -> impl Iterator<Item = Result<(String, KeyValue<'h, B>)>> + 'h {
+        let name = name.to_owned();
+
+        SubtreeKeys::new(self.clone(), TraversalMode::Strict, true, true).filter_map(move |item| {
+            let (path, key_node) = match item {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let key_value = key_node.value(&name)?;
+            Some(key_value.map(|key_value| (path, key_value)))
+        })
+    }
+
+    /// Checks whether this Key Node has multiple subkeys with the same name.
+    ///
+    /// Subkey lists are sorted, so duplicates would be adjacent to each other.
+    /// They only occur in corrupted hives and make lookups by name ambiguous.
+    pub fn has_duplicate_subkeys(&self) -> Result<bool> {
+        let subkeys = match self.subkeys() {
+            Some(subkeys) => subkeys?,
+            None => return Ok(false),
+        };
+
+        let mut previous_key_node: Option<KeyNode<'h, B>> = None;
+
+        for key_node in subkeys {
+            let key_node = key_node?;
+
+            if let Some(previous_key_node) = &previous_key_node {
+                if previous_key_node.name()? == key_node.name()? {
+                    return Ok(true);
+                }
+            }
+
+            previous_key_node = Some(key_node);
+        }
+
+        Ok(false)
+    }
+
+    /// Returns an iterator over the Key Node offsets and stored name hashes of the subkeys of
+    /// this Key Node, if they are managed by a single Hash Leaf.
+    ///
+    /// Returns `None` if this Key Node has no subkeys or they are managed by another structure,
+    /// such as an Index Root.
+    pub fn hash_leaf_entries(&self) -> Option<Result<HashLeafEntries<'h, B>>> {
+        let cell_range = iter_try!(self.item_range.subkeys_cell_range(self.hive)?);
+        let subkeys = iter_try!(SubKeyNodes::new(self.hive, cell_range));
+
+        match subkeys {
+            SubKeyNodes::IndexRoot(_) => None,
+            SubKeyNodes::Leaf(iter) => {
+                let leaf_item_ranges = LeafItemRanges::from(iter);
+                HashLeafEntries::new(self.hive, leaf_item_ranges).map(Ok)
+            }
+        }
+    }
+
+    /// Returns `true` if this Key Node has subkeys.
+    ///
+    /// This only checks the subkey count cached in the header and doesn't enumerate anything.
+    pub fn has_subkeys(&self) -> bool {
+        self.item_range.header(self.hive).subkey_count.get() > 0
+    }
+
+    /// Returns `true` if this Key Node has values.
+    ///
+    /// This only checks the value count cached in the header and doesn't enumerate anything.
+    pub fn has_values(&self) -> bool {
+        self.item_range.header(self.hive).key_values_count.get() > 0
+    }
+
     /// Returns the name of this Key Node.
-    pub fn name(&self) -> Result<NtHiveNameString> {
+    pub fn name(&self) -> Result<NtHiveNameString<'_>> {
         self.item_range.name(self.hive)
     }
 
+    /// Returns `true` if the `KEY_COMP_NAME` flag of this Key Node says that its name is stored
+    /// "compressed" as Latin1, or `false` if it is stored as UTF-16LE.
+    pub fn name_is_compressed(&self) -> bool {
+        let header = self.item_range.header(self.hive);
+        let flags = KeyNodeFlags::from_bits_truncate(header.flags.get());
+        flags.contains(KeyNodeFlags::KEY_COMP_NAME)
+    }
+
+    /// Returns the name of this Key Node, recovering from a UTF-16LE name with an odd byte length.
+    ///
+    /// In that case, the dangling last byte is dropped and the returned flag is `true`.
+    pub fn name_lossy(&self) -> Result<(NtHiveNameString<'_>, bool)> {
+        let name = self.item_range.name(self.hive)?;
+        Ok(name.without_dangling_byte())
+    }
+
+    /// Returns the data offset of the cell holding this Key Node.
+    ///
+    /// It stays valid as long as the hive isn't modified and can be turned back into a
+    /// [`KeyNode`] via [`Hive::key_at`].
+    pub fn offset(&self) -> u32 {
+        (self.item_range.header_range.start - mem::size_of::<CellHeader>()) as u32
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn header(&self) -> Ref<&'h [u8], KeyNodeHeader> {
+        self.item_range.header(self.hive)
+    }
+
+    /// Returns the offset of the cell holding this Key Node from the very beginning of the hive
+    /// bytes, for reporting errors.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn file_offset(&self) -> usize {
+        self.hive.offset_of_data_offset(self.offset() as usize)
+    }
+
+    /// Returns a copy of this [`KeyNode`] that borrows the hive only for the lifetime of `self`.
+    ///
+    /// This is as cheap as [`Clone::clone`] and eases passing a Key Node to helper functions
+    /// that don't need the full hive lifetime.
+    pub fn reborrow(&self) -> KeyNode<'_, B> {
+        self.clone()
+    }
+
+    /// Returns the raw security descriptor of this Key Node (if any).
+    ///
+    /// See [`Hive::security_descriptor_for`] for details.
+    pub fn security_descriptor(&self) -> Option<Result<&'h [u8]>> {
+        let offset = self.security_offset()?;
+        Some(self.hive.security_descriptor_for(offset))
+    }
+
+    /// Returns the data offset of the Key Security cell of this Key Node (if any).
+    ///
+    /// Keys with the same security settings share a Key Security cell and therefore have the
+    /// same offset.
+    pub fn security_offset(&self) -> Option<u32> {
+        let offset = self.item_range.header(self.hive).key_security_offset.get();
+        (offset != u32::MAX).then_some(offset)
+    }
+
+    /// Copies the name, all values, and all subkey names of this Key Node into an owned
+    /// [`KeySnapshot`].
+    #[cfg(feature = "alloc")]
+    pub fn snapshot(&self) -> Result<KeySnapshot> {
+        let mut values = Vec::new();
+
+        if let Some(key_values) = self.values() {
+            for key_value in key_values? {
+                let key_value = key_value?;
+                values.push((
+                    key_value.name()?.to_string_lossy(),
+                    key_value.data_type()?,
+                    key_value.data()?.into_vec()?,
+                ));
+            }
+        }
+
+        Ok(KeySnapshot {
+            name: self.name()?.to_string_lossy(),
+            values,
+            subkey_names: self.subkey_names()?,
+        })
+    }
+
+    /// Returns the raw bytes of the `Spare` and `WorkVar` fields of this Key Node, in that order.
+    pub fn spare_fields(&self) -> [u8; 8] {
+        let header = self.item_range.header(self.hive);
+        let mut spare_fields = [0u8; 8];
+        spare_fields[..4].copy_from_slice(header.spare.as_bytes());
+        spare_fields[4..].copy_from_slice(header.work_var.as_bytes());
+        spare_fields
+    }
+
     /// Finds a single subkey by name using efficient binary search.
     pub fn subkey(&self, name: &str) -> Option<Result<KeyNode<'h, B>>> {
         let item_range = iter_try!(self.item_range.subkey(self.hive, name)?);
@@ -450,12 +682,67 @@ where
         }))
     }
 
+    /// Returns the subkey at position `index` in hive order (or `None` if there are fewer subkeys).
+    ///
+    /// Subkeys Lists are not iterated from the start, but whole Leafs are skipped to reach
+    /// the requested position.
+    pub fn subkey_at(&self, index: usize) -> Option<Result<KeyNode<'h, B>>> {
+        let mut subkeys = iter_try!(self.subkeys()?);
+        subkeys.nth(index)
+    }
+
+    /// Returns the names of all subkeys of this Key Node as owned strings, in hive order.
+    ///
+    /// Names are converted via [`NtHiveNameString::to_string_lossy`].
+    #[cfg(feature = "alloc")]
+    pub fn subkey_names(&self) -> Result<Vec<String>> {
+        let mut subkey_names = Vec::new();
+
+        if let Some(subkeys) = self.subkeys() {
+            for key_node in subkeys? {
+                let key_node = key_node?;
+                subkey_names.push(key_node.name()?.to_string_lossy());
+            }
+        }
+
+        Ok(subkey_names)
+    }
+
     /// Returns an iterator over the subkeys of this Key Node.
     pub fn subkeys(&self) -> Option<Result<SubKeyNodes<'h, B>>> {
         let cell_range = iter_try!(self.item_range.subkeys_cell_range(self.hive)?);
         Some(SubKeyNodes::new(self.hive, cell_range))
     }
 
+    /// Returns an iterator over the subkeys of this Key Node that stops after `max` subkeys.
+    ///
+    /// This caps the work spent on enumerating keys with an excessive number of subkeys,
+    /// e.g. when parsing untrusted hives, and allows sampling the first subkeys.
+    /// In [`TraversalMode::Strict`], a subkey count wildly exceeding `max` (more than
+    /// [`SUBKEYS_LIMITED_STRICT_FACTOR`] times `max`) is rejected with
+    /// [`NtHiveError::TooManySubkeys`] before any subkey is read.
+    #[cfg(feature = "alloc")]
+    pub fn subkeys_limited(
+        &self,
+        max: usize,
+        mode: TraversalMode,
+    ) -> Option<Result<Take<SubKeyNodes<'h, B>>>> {
+        let header = self.item_range.header(self.hive);
+        let subkey_count = header.subkey_count.get();
+        if mode == TraversalMode::Strict
+            && subkey_count as usize > max.saturating_mul(SUBKEYS_LIMITED_STRICT_FACTOR)
+        {
+            return Some(Err(NtHiveError::TooManySubkeys {
+                offset: self.hive.offset_of_field(&header.subkey_count),
+                max,
+                actual: subkey_count,
+            }));
+        }
+
+        let subkeys = iter_try!(self.subkeys()?);
+        Some(Ok(subkeys.take(max)))
+    }
+
     /// Traverses the given subpath and returns the [`KeyNode`] of the last path element.
     ///
     /// Path elements must be separated by backslashes.
@@ -474,28 +761,236 @@ where
         self.item_range.timestamp(self.hive)
     }
 
+    /// Returns an iterator over the name, data type, and data of each value of this Key Node.
+    ///
+    /// This is a shortcut for calling [`KeyValue::name`], [`KeyValue::data_type`], and
+    /// [`KeyValue::data`] on each item of [`KeyNode::values`].
+    /// Values with an unsupported data type result in an error item.
+    pub fn value_entries(
+        &self,
+    ) -> Option<Result<impl Iterator<Item = Result<KeyValueEntry<'h, B>>> + 'h>> {
+        let key_values = iter_try!(self.values()?);
+
+        Some(Ok(key_values.map(|key_value| {
+            let key_value = key_value?;
+            Ok((key_value.name()?, key_value.data_type()?, key_value.data()?))
+        })))
+    }
+
     /// Finds a single value by name.
+    ///
+    /// This scans the unsorted values list linearly, which is fine for a few lookups.
+    /// For many lookups on a key with many values, build the index of [`KeyNode::value_map`]
+    /// once and perform all lookups on it.
     pub fn value(&self, name: &str) -> Option<Result<KeyValue<'h, B>>> {
         self.item_range.value(self.hive, name)
     }
 
+    /// Returns all values of this Key Node in a map keyed by their case-insensitive names.
+    ///
+    /// This allocates an owned copy of each value name, but afterwards allows any number of
+    /// lookups in O(log n) time without rescanning the values list.
+    /// See [`KeyNode::value`] for when this pays off.
+    /// Keys without values result in an empty map.
+    ///
+    /// Two values with the same name can't be represented in the map and result in
+    /// [`NtHiveError::DuplicateValueName`].
+    #[cfg(feature = "alloc")]
+    pub fn value_map(&self) -> Result<BTreeMap<OwnedNtHiveName, KeyValue<'h, B>>> {
+        let mut map = BTreeMap::new();
+
+        if let Some(key_values) = self.values() {
+            for key_value in key_values? {
+                let key_value = key_value?;
+                let offset = self
+                    .hive
+                    .offset_of_data_offset(key_value.cell_offset() as usize);
+
+                if map
+                    .insert(OwnedNtHiveName::from(key_value.name()?), key_value)
+                    .is_some()
+                {
+                    return Err(NtHiveError::DuplicateValueName { offset });
+                }
+            }
+        }
+
+        Ok(map)
+    }
+
     /// Returns an iterator over the values of this Key Node.
     pub fn values(&self) -> Option<Result<KeyValues<'h, B>>> {
         self.item_range.values(self.hive)
     }
+
+    /// Returns all values of this Key Node, sorted case-insensitively by name.
+    ///
+    /// Windows stores values in no particular order, so this collects and sorts them
+    /// to get the same order as regedit.
+    #[cfg(feature = "alloc")]
+    pub fn values_sorted(&self) -> Result<Vec<KeyValue<'h, B>>> {
+        let (values, _) = self.values_sorted_with(TraversalMode::Strict)?;
+        Ok(values)
+    }
+
+    /// Like [`KeyNode::values_sorted`], but lets `mode` determine whether to fail on the first
+    /// erroneous value or to skip it.
+    ///
+    /// Returns the sorted values along with the number of skipped values.
+    #[cfg(feature = "alloc")]
+    pub fn values_sorted_with(&self, mode: TraversalMode) -> Result<(Vec<KeyValue<'h, B>>, usize)> {
+        let mut values = Vec::new();
+        let mut skipped = 0;
+
+        if let Some(iter) = self.values() {
+            for key_value in iter? {
+                let item = key_value.and_then(|key_value| Ok((key_value.name()?, key_value)));
+
+                if let Some(item) = mode.handle(item, &mut skipped) {
+                    values.push(item?);
+                }
+            }
+        }
+
+        values.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let values = values.into_iter().map(|(_, key_value)| key_value).collect();
+        Ok((values, skipped))
+    }
+
+    /// Checks whether the subkey count cached in the header of this Key Node matches the
+    /// number of subkeys actually found in its Subkeys List.
+    ///
+    /// A mismatch indicates a corrupted or tampered hive.
+    pub fn verify_subkey_count(&self) -> Result<bool> {
+        let subkey_count = self.item_range.header(self.hive).subkey_count.get();
+        let mut actual_subkey_count = 0u32;
+
+        // Look at the Subkeys List even if the cached count claims that there are no subkeys.
+        if let Some(cell_range) = self.item_range.subkeys_list_cell_range(self.hive) {
+            for key_node in SubKeyNodes::new(self.hive, cell_range?)? {
+                key_node?;
+                actual_subkey_count += 1;
+            }
+        }
+
+        Ok(subkey_count == actual_subkey_count)
+    }
+}
+
+impl<B> Clone for KeyNode<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hive: self.hive,
+            item_range: self.item_range.clone(),
+        }
+    }
+}
+
+impl<B> Hash for KeyNode<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset().hash(state);
+    }
 }
 
 impl<B> PartialEq for KeyNode<'_, B>
 where
     B: SplitByteSlice,
 {
+    /// Checks whether both handles refer to the same key of the same hive.
     fn eq(&self, other: &Self) -> bool {
-        ptr::eq(self.hive, other.hive) && self.item_range == other.item_range
+        ptr::eq(self.hive, other.hive) && self.offset() == other.offset()
     }
 }
 
 impl<B> Eq for KeyNode<'_, B> where B: SplitByteSlice {}
 
+/// Iterator over
+///   all keys of a hive in breadth-first order, starting at the root key,
+///   returning a constant [`KeyNode`] for each key.
+///
+/// Returned by [`Hive::keys_breadth_first`].
+/// A Key Node reached a second time (e.g. via a subkeys list pointing back to an ancestor) is
+/// reported as [`NtHiveError::KeyNodeCycle`] instead of being visited again.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct KeysBreadthFirst<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    queue: VecDeque<KeyNode<'h, B>>,
+    visited: VisitedKeyNodes,
+    started: bool,
+    finished: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<'h, B> KeysBreadthFirst<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(hive: &'h Hive<B>) -> Self {
+        Self {
+            hive,
+            queue: VecDeque::new(),
+            visited: VisitedKeyNodes::default(),
+            started: false,
+            finished: false,
+        }
+    }
+
+    fn next_key_node(&mut self) -> Option<Result<KeyNode<'h, B>>> {
+        let key_node = if self.started {
+            self.queue.pop_front()?
+        } else {
+            self.started = true;
+            let root_key_node = iter_try!(self.hive.root_key_node());
+            iter_try!(self.visited.insert(&root_key_node));
+            root_key_node
+        };
+
+        // Enqueue the subkeys to return them after all keys of the current level.
+        if let Some(subkeys) = key_node.subkeys() {
+            for subkey in iter_try!(subkeys) {
+                let subkey = iter_try!(subkey);
+                iter_try!(self.visited.insert(&subkey));
+                self.queue.push_back(subkey);
+            }
+        }
+
+        Some(Ok(key_node))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'h, B> Iterator for KeysBreadthFirst<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<KeyNode<'h, B>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_key_node();
+
+        // Stop after the last key or the first error, as the remaining keys may be unreachable.
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        item
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B> FusedIterator for KeysBreadthFirst<'_, B> where B: SplitByteSlice {}
+
 pub(crate) struct KeyNodeMut<'h, B: SplitByteSliceMut> {
     hive: &'h mut Hive<B>,
     item_range: KeyNodeItemRange,
@@ -532,7 +1027,7 @@ where
         Ok(())
     }
 
-    pub(crate) fn subkeys_mut(&mut self) -> Option<Result<SubKeyNodesMut<B>>> {
+    pub(crate) fn subkeys_mut(&mut self) -> Option<Result<SubKeyNodesMut<'_, B>>> {
         let cell_range = iter_try!(self.item_range.subkeys_cell_range(self.hive)?);
         Some(SubKeyNodesMut::new(self.hive, cell_range))
     }
@@ -573,6 +1068,460 @@ mod tests {
         assert!(subkey1 != subkey2);
     }
 
+    #[test]
+    fn test_eq() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        let key_node1 = root_key_node
+            .subpath("subpath-test\\with-two-levels-of-subkeys")
+            .unwrap()
+            .unwrap();
+        let key_node2 = root_key_node
+            .subkey("subpath-test")
+            .unwrap()
+            .unwrap()
+            .subkey("WITH-TWO-LEVELS-OF-SUBKEYS")
+            .unwrap()
+            .unwrap();
+        assert!(key_node1 == key_node2);
+        assert!(key_node1 != root_key_node);
+
+        let mut visited = std::collections::HashSet::new();
+        assert!(visited.insert(key_node1));
+        assert!(!visited.insert(key_node2));
+        assert!(visited.insert(root_key_node));
+
+        // Handles to different hives are never equal, even for the same offset.
+        let other_hive = Hive::new(testhive.as_ref()).unwrap();
+        assert!(hive.root_key_node().unwrap() != other_hive.root_key_node().unwrap());
+    }
+
+    #[test]
+    fn test_find_values_named() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .create_key("data-test\\nested")
+            .set_value(
+                "subpath-test",
+                "InstallLocation",
+                KeyValueDataType::RegSZ,
+                &encode_sz("C:\\A"),
+            )
+            .set_value(
+                "data-test\\nested",
+                "installlocation",
+                KeyValueDataType::RegSZ,
+                &encode_sz("C:\\B"),
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // Both values are found regardless of the case of their names.
+        let found = root_key_node
+            .find_values_named("INSTALLLOCATION")
+            .map(|item| {
+                let (path, key_value) = item.unwrap();
+                (path, key_value.string_data().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                ("data-test\\nested".to_owned(), "C:\\B".to_owned()),
+                ("subpath-test".to_owned(), "C:\\A".to_owned()),
+            ]
+        );
+
+        // Paths are relative to the starting Key Node.
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let found = key_node
+            .find_values_named("InstallLocation")
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["nested"]);
+
+        // Values of the starting Key Node itself have an empty path.
+        let key_node = root_key_node.subpath("data-test\\nested").unwrap().unwrap();
+        let found = key_node
+            .find_values_named("InstallLocation")
+            .map(|item| item.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(found, [""]);
+
+        assert_eq!(root_key_node.find_values_named("nonexistent").count(), 0);
+    }
+
+    #[test]
+    fn test_has_duplicate_subkeys() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        assert!(!root_key_node.has_duplicate_subkeys().unwrap());
+
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(!key_node.has_duplicate_subkeys().unwrap());
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(!key_node.has_duplicate_subkeys().unwrap());
+
+        // Rename the second of two subkeys to a differently cased copy of the first one.
+        let mut patched = HivePatch::new()
+            .create_key("duplicates\\dup-a")
+            .create_key("duplicates\\dup-b")
+            .apply(&hive)
+            .unwrap();
+        let key_node_offset = {
+            let hive = Hive::new(patched.as_ref()).unwrap();
+            let key_node = hive
+                .root_key_node()
+                .unwrap()
+                .subpath("duplicates\\dup-b")
+                .unwrap()
+                .unwrap();
+            key_node.offset() as usize
+        };
+        let name_offset = 4096
+            + key_node_offset
+            + core::mem::size_of::<i32>()
+            + core::mem::size_of::<super::KeyNodeHeader>();
+        patched[name_offset..name_offset + 5].copy_from_slice(b"DUP-A");
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let key_node = hive
+            .root_key_node()
+            .unwrap()
+            .subkey("duplicates")
+            .unwrap()
+            .unwrap();
+        assert!(key_node.has_duplicate_subkeys().unwrap());
+    }
+
+    #[test]
+    fn test_hash_leaf_entries() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // The stored hashes match the hashes of the actual key names.
+        let key_node = root_key_node.subkey("subpath-test").unwrap().unwrap();
+        let entries = key_node.hash_leaf_entries().unwrap().unwrap();
+        assert_eq!(entries.len(), 3);
+
+        for ((key_node_offset, stored_hash), subkey) in
+            entries.zip(key_node.subkeys().unwrap().unwrap())
+        {
+            let subkey = subkey.unwrap();
+            let name = subkey.name().unwrap().to_string_lossy();
+            let code_units = name.encode_utf16().collect::<Vec<_>>();
+            assert_eq!(key_node_offset, subkey.offset());
+            assert_eq!(stored_hash, crate::leaf::name_hash(&code_units));
+        }
+
+        // Index Roots and keys without subkeys have no Hash Leaf.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.hash_leaf_entries().is_none());
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(key_node.hash_leaf_entries().is_none());
+    }
+
+    #[test]
+    fn test_has_subkeys_and_values() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // An interior key without values.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.has_subkeys());
+        assert!(!key_node.has_values());
+
+        // A leaf key with values.
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(!key_node.has_subkeys());
+        assert!(key_node.has_values());
+
+        // A leaf key without values.
+        let key_node = root_key_node.subpath("subkey-test\\key0").unwrap().unwrap();
+        assert!(!key_node.has_subkeys());
+        assert!(!key_node.has_values());
+    }
+
+    #[test]
+    fn test_name_is_compressed() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node
+            .subkey("character-encoding-test")
+            .unwrap()
+            .unwrap();
+        assert!(key_node.name_is_compressed());
+
+        // Latin1 names are compressed, others are not.
+        let subkey = key_node.subkey("äöü").unwrap().unwrap();
+        assert!(subkey.name_is_compressed());
+        let subkey = key_node.subkey("Ａ").unwrap().unwrap();
+        assert!(!subkey.name_is_compressed());
+        assert!(matches!(
+            subkey.name().unwrap(),
+            NtHiveNameString::Utf16LE(_)
+        ));
+
+        // The same applies to Key Values.
+        let patched = HivePatch::new()
+            .set_value("data-test", "Ａ", KeyValueDataType::RegDWord, &[0; 4])
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(key_node
+            .value("dword")
+            .unwrap()
+            .unwrap()
+            .name_is_compressed());
+        assert!(!key_node.value("Ａ").unwrap().unwrap().name_is_compressed());
+    }
+
+    #[test]
+    fn test_name_lossy() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let mut patched = HivePatch::new()
+            .create_key("\u{10410}\u{10438}")
+            .apply(&hive)
+            .unwrap();
+
+        let key_node_offset = {
+            let hive = Hive::new(patched.as_ref()).unwrap();
+            let root_key_node = hive.root_key_node().unwrap();
+
+            // Latin1 names are returned unchanged.
+            let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+            let (name, dropped) = key_node.name_lossy().unwrap();
+            assert_eq!(name, "data-test");
+            assert!(!dropped);
+
+            let key_node = root_key_node.subkey("\u{10410}\u{10438}").unwrap().unwrap();
+            let (name, dropped) = key_node.name_lossy().unwrap();
+            assert_eq!(name.len(), 8);
+            assert!(!dropped);
+
+            key_node.offset() as usize
+        };
+
+        // Cut off the last byte of the UTF-16LE name.
+        let key_name_length_offset = 4096
+            + key_node_offset
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyNodeHeader, key_name_length);
+        patched[key_name_length_offset] = 7;
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node
+            .subkeys()
+            .unwrap()
+            .unwrap()
+            .find(|key_node| key_node.as_ref().unwrap().name().unwrap().len() == 7)
+            .unwrap()
+            .unwrap();
+
+        let (name, dropped) = key_node.name_lossy().unwrap();
+        assert_eq!(name.len(), 6);
+        assert_eq!(name.to_string_lossy(), "\u{10410}\u{FFFD}");
+        assert!(dropped);
+    }
+
+    #[test]
+    fn test_offset() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        assert_eq!(root_key_node.offset(), hive.base_block().root_cell_offset());
+
+        let key_node = root_key_node
+            .subpath("subpath-test\\with-two-levels-of-subkeys\\subkey1")
+            .unwrap()
+            .unwrap();
+        let bookmarked_key_node = hive.key_at(key_node.offset()).unwrap();
+        assert!(bookmarked_key_node == key_node);
+        assert_eq!(bookmarked_key_node.name().unwrap(), "subkey1");
+
+        // Offsets not pointing to a Key Node must be rejected.
+        let key_value = root_key_node
+            .subpath("data-test")
+            .unwrap()
+            .unwrap()
+            .value("binary")
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            hive.key_at(key_value.cell_offset()),
+            Err(NtHiveError::InvalidHeaderSize { .. } | NtHiveError::InvalidTwoByteSignature { .. })
+        ));
+        assert!(hive.key_at(u32::MAX).is_err());
+        assert!(hive.key_at(1).is_err());
+    }
+
+    #[test]
+    fn test_reborrow() {
+        fn subkey_count<B: zerocopy::SplitByteSlice>(key_node: KeyNode<'_, B>) -> usize {
+            key_node
+                .subkeys()
+                .map_or(0, |subkeys| subkeys.unwrap().count())
+        }
+
+        fn value_name<B: zerocopy::SplitByteSlice>(key_value: KeyValue<'_, B>) -> String {
+            key_value.name().unwrap().to_string_lossy()
+        }
+
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+
+        // The original handles remain usable after passing reborrowed ones.
+        assert_eq!(subkey_count(key_node.reborrow()), 512);
+        assert!(key_node.reborrow() == key_node);
+        assert_eq!(subkey_count(key_node), 512);
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(value_name(key_value.reborrow()), "dword");
+        assert!(key_value.reborrow() == key_value);
+        assert_eq!(value_name(key_value), "dword");
+    }
+
+    #[test]
+    fn test_security_descriptor() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node_1 = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_node_2 = root_key_node.subkey("subkey-test").unwrap().unwrap();
+
+        // Both keys share a single Key Security cell and therefore get the same bytes.
+        let security_offset = key_node_1.security_offset().unwrap();
+        assert_eq!(key_node_2.security_offset(), Some(security_offset));
+
+        let security_descriptor_1 = key_node_1.security_descriptor().unwrap().unwrap();
+        let security_descriptor_2 = key_node_2.security_descriptor().unwrap().unwrap();
+        assert!(core::ptr::eq(security_descriptor_1, security_descriptor_2));
+        assert_eq!(
+            hive.security_descriptor_for(security_offset).unwrap(),
+            security_descriptor_1
+        );
+
+        // A self-relative security descriptor starts with revision 1.
+        assert!(security_descriptor_1.len() >= 20);
+        assert_eq!(security_descriptor_1[0], 1);
+
+        // Other cells are rejected.
+        assert!(matches!(
+            hive.security_descriptor_for(key_node_1.offset()),
+            Err(NtHiveError::InvalidTwoByteSignature {
+                context: "key security",
+                ..
+            })
+        ));
+
+        // Offsets outside the hive data are rejected just like by `Hive::key_at`.
+        for offset in [u32::MAX, (testhive.len() - 4096) as u32] {
+            let expected_error = NtHiveError::InvalidHeaderSize {
+                context: "key security",
+                offset: 4096 + offset as usize,
+                expected: 4,
+                actual: 0,
+            };
+            assert_eq!(hive.security_descriptor_for(offset), Err(expected_error));
+
+            assert!(matches!(
+                hive.key_at(offset),
+                Err(NtHiveError::InvalidHeaderSize {
+                    context: "key node",
+                    actual: 0,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let snapshot = key_node.snapshot().unwrap();
+
+        assert_eq!(snapshot.name, "data-test");
+        assert!(snapshot.subkey_names.is_empty());
+        assert_eq!(
+            snapshot.values.len(),
+            key_node.values().unwrap().unwrap().count()
+        );
+
+        let (_, data_type, data) = snapshot
+            .values
+            .iter()
+            .find(|(name, _, _)| name == "dword")
+            .unwrap();
+        assert_eq!(*data_type, KeyValueDataType::RegDWord);
+        assert_eq!(data, &42u32.to_le_bytes());
+
+        let (_, data_type, data) = snapshot
+            .values
+            .iter()
+            .find(|(name, _, _)| name == "reg-multi-sz-big")
+            .unwrap();
+        assert_eq!(*data_type, KeyValueDataType::RegMultiSZ);
+        assert_eq!(data.len(), (820 * 10 + 1 + 10 + 1 + 1) * 2);
+
+        // The snapshot outlives the hive.
+        let snapshot = {
+            let hive = Hive::new(testhive.as_ref()).unwrap();
+            hive.root_key_node().unwrap().snapshot().unwrap()
+        };
+        assert_eq!(snapshot.name, "ROOT");
+        assert!(snapshot.values.is_empty());
+        assert_eq!(
+            snapshot.subkey_names,
+            [
+                "big-data-test",
+                "character-encoding-test",
+                "data-test",
+                "subkey-test",
+                "subpath-test"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spare_fields() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert_eq!(key_node.spare_fields(), [0; 8]);
+
+        let header_offset = 4096 + key_node.offset() as usize + 4;
+        let spare_offset = header_offset + memoffset::offset_of!(super::KeyNodeHeader, spare);
+        let work_var_offset = header_offset + memoffset::offset_of!(super::KeyNodeHeader, work_var);
+        testhive[spare_offset..spare_offset + 4].copy_from_slice(&[1, 2, 3, 4]);
+        testhive[work_var_offset..work_var_offset + 4].copy_from_slice(&[5, 6, 7, 8]);
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert_eq!(key_node.spare_fields(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
     #[test]
     fn test_subkey() {
         // Prove that our binary search algorithm finds every subkey of "subkey-test".
@@ -590,6 +1539,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subkey_at() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // "subkey-test" uses an Index Root, "subpath-test" a single Leaf.
+        for key_name in ["subkey-test", "subpath-test"] {
+            let key_node = root_key_node.subkey(key_name).unwrap().unwrap();
+            let subkey_names = key_node.subkey_names().unwrap();
+            let middle = subkey_names.len() / 2;
+
+            for index in [0, middle, subkey_names.len() - 1] {
+                let subkey = key_node.subkey_at(index).unwrap().unwrap();
+                assert_eq!(subkey.name().unwrap(), subkey_names[index].as_str());
+            }
+
+            assert!(key_node.subkey_at(subkey_names.len()).is_none());
+            assert!(key_node.subkey_at(usize::MAX).is_none());
+        }
+
+        // Every position of a multi-Leaf Index Root resolves to the same key as iterating.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        for (index, subkey) in key_node.subkeys().unwrap().unwrap().enumerate() {
+            assert!(key_node.subkey_at(index).unwrap().unwrap() == subkey.unwrap());
+        }
+
+        // Keys without subkeys have nothing at any position.
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(key_node.subkey_at(0).is_none());
+    }
+
+    #[test]
+    fn test_subkey_names() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let mut subkey_names = root_key_node.subkey_names().unwrap();
+        subkey_names.sort_unstable();
+        assert_eq!(
+            subkey_names,
+            [
+                "big-data-test",
+                "character-encoding-test",
+                "data-test",
+                "subkey-test",
+                "subpath-test"
+            ]
+        );
+
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        let subkey_names = key_node.subkey_names().unwrap();
+        assert_eq!(subkey_names.len(), 512);
+        assert!(subkey_names[0].eq_ignore_ascii_case("key0"));
+    }
+
     #[test]
     fn test_subkeys() {
         // Keep in mind that subkeys in the hive are sorted like key0, key1, key10, key11, ...
@@ -613,6 +1618,87 @@ mod tests {
             let subkey = subkey.unwrap();
             assert_eq!(subkey.name().unwrap(), expected_key_name.as_str());
         }
+
+        // A key without subkeys has no Subkeys List to resolve.
+        let key_node = root_key_node
+            .subpath("subpath-test\\no-subkeys")
+            .unwrap()
+            .unwrap();
+        assert!(key_node.subkeys().is_none());
+        assert!(key_node.subkey("anything").is_none());
+
+        // The Subkeys List offset is ignored if the subkey count is zero, as it may be bogus.
+        let subkeys_list_offset = 4096
+            + key_node.offset() as usize
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyNodeHeader, subkeys_list_offset);
+        let mut patched = testhive.clone();
+        patched[subkeys_list_offset..subkeys_list_offset + 4]
+            .copy_from_slice(&0x1234_5678u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node
+            .subpath("subpath-test\\no-subkeys")
+            .unwrap()
+            .unwrap();
+        assert!(key_node.subkeys().is_none());
+        assert!(key_node.subkey("anything").is_none());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_subkeys_limited() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+
+        // Lenient mode returns exactly the first 2 of 512 subkeys.
+        let mut subkeys = key_node.subkeys().unwrap().unwrap();
+        let limited_subkeys = key_node
+            .subkeys_limited(2, TraversalMode::Lenient)
+            .unwrap()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(limited_subkeys.len(), 2);
+        for subkey in limited_subkeys {
+            assert!(subkey == subkeys.next().unwrap().unwrap());
+        }
+
+        let subkeys = key_node
+            .subkeys_limited(1000, TraversalMode::Lenient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(subkeys.count(), 512);
+
+        // Strict mode rejects a subkey count of more than 16 times the maximum up front.
+        let subkey_count_offset = 4096
+            + key_node.offset() as usize
+            + 4
+            + memoffset::offset_of!(super::KeyNodeHeader, subkey_count);
+        assert_eq!(
+            key_node
+                .subkeys_limited(2, TraversalMode::Strict)
+                .unwrap()
+                .err(),
+            Some(NtHiveError::TooManySubkeys {
+                offset: subkey_count_offset,
+                max: 2,
+                actual: 512,
+            })
+        );
+
+        let subkeys = key_node
+            .subkeys_limited(32, TraversalMode::Strict)
+            .unwrap()
+            .unwrap();
+        assert_eq!(subkeys.count(), 32);
+
+        // Keys without subkeys have nothing to limit.
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(key_node.subkeys_limited(2, TraversalMode::Strict).is_none());
     }
 
     #[test]
@@ -660,4 +1746,240 @@ mod tests {
         assert!(key_node.subpath("non-existing").is_none());
         assert!(key_node.subpath("non-existing\\sub").is_none());
     }
+
+    #[test]
+    fn test_value_entries() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let entries = key_node
+            .value_entries()
+            .unwrap()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let key_values = key_node.values().unwrap().unwrap();
+        assert_eq!(entries.len(), key_values.len());
+
+        for ((name, data_type, data), key_value) in entries.into_iter().zip(key_values) {
+            let key_value = key_value.unwrap();
+            assert_eq!(name, key_value.name().unwrap());
+            assert_eq!(data_type, key_value.data_type().unwrap());
+            assert_eq!(
+                data.into_vec().unwrap(),
+                key_value.data().unwrap().into_vec().unwrap()
+            );
+        }
+
+        let (_, data_type, data) = key_node
+            .value_entries()
+            .unwrap()
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .find(|(name, _, _)| name == "binary")
+            .unwrap();
+        assert_eq!(data_type, KeyValueDataType::RegBinary);
+        assert_eq!(data.into_vec().unwrap(), [1, 2, 3, 4, 5]);
+
+        // Keys without values have no entries.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.value_entries().is_none());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_value_map() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let map = key_node.value_map().unwrap();
+        assert_eq!(map.len(), key_node.values().unwrap().unwrap().len());
+
+        let key_value = &map[&OwnedNtHiveName::from("DWORD")];
+        assert_eq!(key_value.dword_data().unwrap(), 42);
+
+        let key_value = &map[&OwnedNtHiveName::from("reg-sz")];
+        assert_eq!(key_value.string_data().unwrap(), "sz-test");
+
+        let key_value = &map[&OwnedNtHiveName::from("Binary")];
+        assert_eq!(
+            key_value.data().unwrap().into_vec().unwrap(),
+            [1, 2, 3, 4, 5]
+        );
+
+        assert!(!map.contains_key(&OwnedNtHiveName::from("non-existing")));
+
+        // Keys without values result in an empty map.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.value_map().unwrap().is_empty());
+
+        // Let the second item of the values list refer to the first value as well.
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let first_key_value = key_node.values().unwrap().unwrap().next().unwrap().unwrap();
+        let list_offset_offset = 4096
+            + key_node.offset() as usize
+            + 4
+            + memoffset::offset_of!(super::KeyNodeHeader, key_values_list_offset);
+        let list_offset = u32::from_le_bytes(
+            testhive[list_offset_offset..list_offset_offset + 4]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let mut patched = testhive.clone();
+        let items_offset = 4096 + list_offset + 4;
+        patched.copy_within(items_offset..items_offset + 4, items_offset + 4);
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let key_node = hive.key_at(key_node.offset()).unwrap();
+        assert_eq!(
+            key_node.value_map().err(),
+            Some(NtHiveError::DuplicateValueName {
+                offset: 4096 + first_key_value.cell_offset() as usize,
+            })
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_value_map_agrees_with_value() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        let mut patch = HivePatch::new();
+        patch.create_key("many-values");
+        for i in 0..300u32 {
+            let name = format!("Value{i}");
+            patch.set_value(
+                "many-values",
+                &name,
+                KeyValueDataType::RegDWord,
+                &i.to_le_bytes(),
+            );
+        }
+        let patched = patch.apply(&hive).unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        for key_name in ["data-test", "many-values"] {
+            let key_node = root_key_node.subkey(key_name).unwrap().unwrap();
+            let map = key_node.value_map().unwrap();
+
+            for key_value in key_node.values().unwrap().unwrap() {
+                let name = key_value.unwrap().name().unwrap().to_string_lossy();
+                let uppercase_name = name.to_uppercase();
+
+                let linear = key_node.value(&uppercase_name).unwrap().unwrap();
+                let indexed = &map[&OwnedNtHiveName::from(uppercase_name.as_str())];
+                assert_eq!(&linear, indexed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_values_sorted() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let names = key_node
+            .values_sorted()
+            .unwrap()
+            .iter()
+            .map(|key_value| key_value.name().unwrap().to_string_lossy())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            names,
+            [
+                "binary",
+                "dword",
+                "dword-big-endian",
+                "qword",
+                "reg-expand-sz",
+                "reg-multi-sz",
+                "reg-multi-sz-big",
+                "reg-sz",
+                "reg-sz-with-terminating-nul",
+            ]
+        );
+
+        // A key without values results in an empty list.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.values_sorted().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_values_without_list() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.values().is_none());
+
+        // Prove that a bogus list offset is ignored when the value count is zero.
+        let mut patched = HivePatch::new()
+            .create_key("no-values")
+            .apply(&hive)
+            .unwrap();
+        let key_node_offset = {
+            let hive = Hive::new(patched.as_ref()).unwrap();
+            let root_key_node = hive.root_key_node().unwrap();
+            let key_node = root_key_node.subkey("no-values").unwrap().unwrap();
+            key_node.offset() as usize
+        };
+        let key_values_list_offset = 4096
+            + key_node_offset
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyNodeHeader, key_values_list_offset);
+        patched[key_values_list_offset..key_values_list_offset + 4]
+            .copy_from_slice(&0x1234_5678u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("no-values").unwrap().unwrap();
+        assert!(key_node.values().is_none());
+        assert!(key_node.value("anything").is_none());
+        assert!(key_node.values_sorted().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_verify_subkey_count() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        assert!(root_key_node.verify_subkey_count().unwrap());
+
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.verify_subkey_count().unwrap());
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(key_node.verify_subkey_count().unwrap());
+
+        // Inflate the cached subkey count of "subkey-test".
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        let subkey_count_offset = 4096
+            + key_node.offset() as usize
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyNodeHeader, subkey_count);
+        let mut patched = testhive.clone();
+        patched[subkey_count_offset..subkey_count_offset + 4]
+            .copy_from_slice(&513u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(!key_node.verify_subkey_count().unwrap());
+
+        // A zeroed subkey count hides the subkeys, but is detected as well.
+        patched[subkey_count_offset..subkey_count_offset + 4].copy_from_slice(&0u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert!(key_node.subkeys().is_none());
+        assert!(!key_node.verify_subkey_count().unwrap());
+    }
 }
@@ -0,0 +1,175 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::Range;
+
+use zerocopy::byteorder::LittleEndian;
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout, Ref, SplitByteSlice, Unaligned, U16, U32,
+};
+
+use crate::error::{NtHiveError, Result};
+use crate::helpers::byte_subrange;
+use crate::hive::Hive;
+
+/// On-Disk Structure of a Key Security header.
+#[allow(dead_code)]
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+pub(crate) struct KeySecurityHeader {
+    pub(crate) signature: [u8; 2],
+    pub(crate) reserved: U16<LittleEndian>,
+    pub(crate) flink: U32<LittleEndian>,
+    pub(crate) blink: U32<LittleEndian>,
+    pub(crate) reference_count: U32<LittleEndian>,
+    pub(crate) security_descriptor_size: U32<LittleEndian>,
+}
+
+/// Iterator over
+///   all Key Security cells of a hive, following their `flink` fields,
+///   returning the security descriptor of each cell.
+///
+/// Returned by [`Hive::security_descriptors`].
+/// The iteration stops after returning to the first cell or after the first error.
+/// Every visited cell must link back to its predecessor via its `blink` field.
+/// This also guarantees that no cell is visited twice, because the first cell reached again
+/// would have two predecessors.
+///
+/// On-Disk Signature: `sk`
+pub(crate) struct SecurityDescriptors<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    first_offset: u32,
+    previous_offset: Option<u32>,
+    next_offset: Option<u32>,
+}
+
+impl<'h, B> SecurityDescriptors<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(hive: &'h Hive<B>, first_offset: Option<u32>) -> Self {
+        Self {
+            hive,
+            first_offset: first_offset.unwrap_or(u32::MAX),
+            previous_offset: None,
+            next_offset: first_offset,
+        }
+    }
+}
+
+impl<'h, B> Iterator for SecurityDescriptors<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<&'h [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Stop after an error, as we cannot follow the list any further then.
+        let offset = self.next_offset.take()?;
+
+        let cell_range = iter_try!(self.hive.cell_range_from_data_offset(offset));
+        let header_range = iter_try!(header_range(self.hive, &cell_range));
+        let header =
+            Ref::<&[u8], KeySecurityHeader>::from_bytes(&self.hive.data[header_range.clone()])
+                .unwrap();
+
+        if let Some(previous_offset) = self.previous_offset {
+            let blink = header.blink.get();
+            if blink != previous_offset {
+                return Some(Err(NtHiveError::InvalidKeySecurityBlink {
+                    offset: self.hive.offset_of_field(&header.blink),
+                    expected: previous_offset,
+                    actual: blink,
+                }));
+            }
+        }
+
+        let security_descriptor_range = iter_try!(security_descriptor_range_after_header(
+            self.hive,
+            &cell_range,
+            &header_range
+        ));
+
+        let flink = header.flink.get();
+        if flink != self.first_offset {
+            self.previous_offset = Some(offset);
+            self.next_offset = Some(flink);
+        }
+
+        Some(Ok(&self.hive.data[security_descriptor_range]))
+    }
+}
+
+impl<B> FusedIterator for SecurityDescriptors<'_, B> where B: SplitByteSlice {}
+
+/// Checks the signature of the Key Security cell at `cell_range` and returns the range of
+/// its header.
+fn header_range<B>(hive: &Hive<B>, cell_range: &Range<usize>) -> Result<Range<usize>>
+where
+    B: SplitByteSlice,
+{
+    let header_range =
+        byte_subrange(cell_range, mem::size_of::<KeySecurityHeader>()).ok_or_else(|| {
+            NtHiveError::InvalidHeaderSize {
+                context: "key security",
+                offset: hive.offset_of_data_offset(cell_range.start),
+                expected: mem::size_of::<KeySecurityHeader>(),
+                actual: cell_range.len(),
+            }
+        })?;
+    let header =
+        Ref::<&[u8], KeySecurityHeader>::from_bytes(&hive.data[header_range.clone()]).unwrap();
+
+    let signature = &header.signature;
+    let expected_signature = b"sk";
+    if signature != expected_signature {
+        return Err(NtHiveError::InvalidTwoByteSignature {
+            context: "key security",
+            offset: hive.offset_of_field(signature),
+            expected: expected_signature,
+            actual: *signature,
+        });
+    }
+
+    Ok(header_range)
+}
+
+/// Returns the range of the security descriptor stored in the Key Security cell at
+/// `cell_range`.
+///
+/// On-Disk Signature: `sk`
+pub(crate) fn security_descriptor_range<B>(
+    hive: &Hive<B>,
+    cell_range: Range<usize>,
+) -> Result<Range<usize>>
+where
+    B: SplitByteSlice,
+{
+    let header_range = header_range(hive, &cell_range)?;
+    security_descriptor_range_after_header(hive, &cell_range, &header_range)
+}
+
+fn security_descriptor_range_after_header<B>(
+    hive: &Hive<B>,
+    cell_range: &Range<usize>,
+    header_range: &Range<usize>,
+) -> Result<Range<usize>>
+where
+    B: SplitByteSlice,
+{
+    let header =
+        Ref::<&[u8], KeySecurityHeader>::from_bytes(&hive.data[header_range.clone()]).unwrap();
+
+    let security_descriptor_size = header.security_descriptor_size.get() as usize;
+    let remaining_range = header_range.end..cell_range.end;
+    byte_subrange(&remaining_range, security_descriptor_size).ok_or_else(|| {
+        NtHiveError::InvalidSizeField {
+            context: "key security",
+            offset: hive.offset_of_field(&header.security_descriptor_size),
+            expected: security_descriptor_size,
+            actual: remaining_range.len(),
+        }
+    })
+}
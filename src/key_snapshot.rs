@@ -0,0 +1,26 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::key_value::KeyValueDataType;
+
+/// Owned copy of the contents of a single [`KeyNode`], detached from the hive lifetime.
+///
+/// Returned by [`KeyNode::snapshot`].
+/// This is useful for passing a key across a thread or FFI boundary.
+/// Names are converted via [`NtHiveNameString::to_string_lossy`].
+///
+/// [`KeyNode`]: crate::key_node::KeyNode
+/// [`KeyNode::snapshot`]: crate::key_node::KeyNode::snapshot
+/// [`NtHiveNameString::to_string_lossy`]: crate::string::NtHiveNameString::to_string_lossy
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeySnapshot {
+    /// Name of the key.
+    pub name: String,
+    /// Name, data type, and raw data of each value, in hive order.
+    pub values: Vec<(String, KeyValueDataType, Vec<u8>)>,
+    /// Names of all subkeys, in hive order.
+    pub subkey_names: Vec<String>,
+}
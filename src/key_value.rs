@@ -1,6 +1,9 @@
 // Copyright 2020-2025 Colin Finck <colin@reactos.org>
 // SPDX-License-Identifier: GPL-2.0-or-later
 
+use core::cmp;
+use core::fmt;
+use core::iter;
 use core::mem;
 use core::ops::Range;
 use core::ptr;
@@ -16,24 +19,27 @@ use zerocopy::{
 use crate::big_data::{BigDataSlices, BIG_DATA_SEGMENT_SIZE};
 use crate::error::{NtHiveError, Result};
 use crate::helpers::byte_subrange;
-use crate::hive::Hive;
+use crate::hive::{Hive, HiveFeature};
+use crate::resource_list::CmResourceList;
 use crate::string::NtHiveNameString;
 
 #[cfg(feature = "alloc")]
 use {
-    alloc::{string::String, vec::Vec},
+    crate::hive::CellHeader,
+    alloc::{borrow::Cow, string::String, vec, vec::Vec},
     core::{
         char::{self, DecodeUtf16, DecodeUtf16Error},
-        iter::{self, FusedIterator, Map},
+        hash::{Hash, Hasher},
+        iter::{FusedIterator, Map},
         slice::ChunksExact,
     },
 };
 
 /// This bit in `data_size` indicates that the data is small enough to be stored in `data_offset`.
-const DATA_STORED_IN_DATA_OFFSET: u32 = 0x8000_0000;
+pub(crate) const DATA_STORED_IN_DATA_OFFSET: u32 = 0x8000_0000;
 
 bitflags! {
-    struct KeyValueFlags: u16 {
+    pub(crate) struct KeyValueFlags: u16 {
         /// The name is in (extended) ASCII instead of UTF-16LE.
         const VALUE_COMP_NAME = 0x0001;
     }
@@ -50,10 +56,57 @@ pub enum KeyValueData<'h, B: SplitByteSlice> {
     Big(BigDataSlices<'h, B>),
 }
 
-impl<B> KeyValueData<'_, B>
+impl<'h, B> KeyValueData<'h, B>
 where
     B: SplitByteSlice,
 {
+    /// Copies the data bytes into `out` without allocating and returns the number of copied bytes.
+    ///
+    /// Returns [`NtHiveError::BufferTooSmall`] if the data doesn't fit into `out`.
+    pub fn copy_to_slice(&self, out: &mut [u8]) -> Result<usize> {
+        let out_len = out.len();
+
+        match self {
+            KeyValueData::Small(data) => {
+                let dest = out
+                    .get_mut(..data.len())
+                    .ok_or(NtHiveError::BufferTooSmall {
+                        expected: data.len(),
+                        actual: out_len,
+                    })?;
+                dest.copy_from_slice(data);
+                Ok(data.len())
+            }
+            KeyValueData::Big(iter) => {
+                let mut copied = 0;
+                let mut iter = iter.clone();
+
+                while let Some(slice_data) = iter.next() {
+                    let slice_data = slice_data?;
+                    let end = copied + slice_data.len();
+
+                    let Some(dest) = out.get_mut(copied..end) else {
+                        // Determine the total size for the error.
+                        let mut expected = end;
+                        for slice_data in iter {
+                            expected += slice_data?.len();
+                        }
+
+                        return Err(NtHiveError::BufferTooSmall {
+                            expected,
+                            actual: out_len,
+                        });
+                    };
+
+                    dest.copy_from_slice(slice_data);
+                    copied = end;
+                }
+
+                Ok(copied)
+            }
+        }
+    }
+
     #[cfg(feature = "alloc")]
     pub fn into_vec(self) -> Result<Vec<u8>> {
         match self {
@@ -70,8 +123,205 @@ where
             }
         }
     }
+
+    /// Like [`KeyValueData::into_vec`], but substitutes zeros for Big Data segments that cannot
+    /// be read instead of failing.
+    ///
+    /// Returns the data along with the number of substituted segments.
+    /// This salvages the intact parts of data from corrupted hives.
+    #[cfg(feature = "alloc")]
+    pub fn into_vec_lossy(self) -> (Vec<u8>, usize) {
+        match self {
+            KeyValueData::Small(data) => (data.to_vec(), 0),
+            KeyValueData::Big(mut iter) => {
+                let mut data = Vec::new();
+                let mut failed_segments = 0;
+
+                loop {
+                    let segment_size = iter.next_segment_size();
+                    let Some(slice_data) = iter.next() else {
+                        break;
+                    };
+
+                    match slice_data {
+                        Ok(slice_data) => data.extend_from_slice(slice_data),
+                        Err(_) => {
+                            data.resize(data.len() + segment_size, 0);
+                            failed_segments += 1;
+                        }
+                    }
+                }
+
+                (data, failed_segments)
+            }
+        }
+    }
+
+    /// Returns an iterator over the data segments, regardless of how the data is stored.
+    ///
+    /// [`KeyValueData::Small`] data is returned as a single segment, even if it is empty.
+    /// [`KeyValueData::Big`] data is returned segment by segment, as described for
+    /// [`BigDataSlices`].
+    pub fn segments(self) -> impl Iterator<Item = Result<&'h [u8]>> {
+        let (small, big) = match self {
+            KeyValueData::Small(data) => (Some(Ok(data)), None),
+            KeyValueData::Big(iter) => (None, Some(iter)),
+        };
+
+        small.into_iter().chain(big.into_iter().flatten())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B> KeyValueData<'_, B>
+where
+    B: SplitByteSlice,
+{
+    /// Returns the total number of data bytes, regardless of how the data is stored.
+    fn total_len(&self) -> usize {
+        match self {
+            KeyValueData::Small(data) => data.len(),
+            KeyValueData::Big(iter) => iter.bytes_left(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B> Eq for KeyValueData<'_, B> where B: SplitByteSlice {}
+
+#[cfg(feature = "alloc")]
+impl<B> Hash for KeyValueData<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.total_len());
+
+        // Feed the hasher with blocks of a fixed size, so that the hash doesn't depend on
+        // where the data is split into segments.
+        let mut reader = LossyDataReader::new(self);
+        let mut buf = [0u8; DATA_COMPARE_BLOCK_SIZE];
+
+        loop {
+            let filled = reader.fill(&mut buf);
+            if filled == 0 {
+                break;
+            }
+
+            state.write(&buf[..filled]);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B> PartialEq for KeyValueData<'_, B>
+where
+    B: SplitByteSlice,
+{
+    /// Checks that two [`KeyValueData`] have the same byte content, regardless of whether they
+    /// are stored in a single cell or in Big Data segments.
+    ///
+    /// Big Data segments that cannot be read are treated as zeros, just like
+    /// [`KeyValueData::into_vec_lossy`] does.
+    fn eq(&self, other: &Self) -> bool {
+        if self.total_len() != other.total_len() {
+            return false;
+        }
+
+        let mut lhs = LossyDataReader::new(self);
+        let mut rhs = LossyDataReader::new(other);
+        let mut lhs_buf = [0u8; DATA_COMPARE_BLOCK_SIZE];
+        let mut rhs_buf = [0u8; DATA_COMPARE_BLOCK_SIZE];
+
+        loop {
+            let lhs_filled = lhs.fill(&mut lhs_buf);
+            let rhs_filled = rhs.fill(&mut rhs_buf);
+
+            if lhs_buf[..lhs_filled] != rhs_buf[..rhs_filled] {
+                return false;
+            }
+
+            if lhs_filled == 0 {
+                return true;
+            }
+        }
+    }
+}
+
+/// Number of bytes that are compared or hashed at once by the [`KeyValueData`] trait implementations.
+#[cfg(feature = "alloc")]
+const DATA_COMPARE_BLOCK_SIZE: usize = 256;
+
+/// Reads the bytes of a [`KeyValueData`] across all segments, substituting zeros for
+/// Big Data segments that cannot be read.
+#[cfg(feature = "alloc")]
+struct LossyDataReader<'h, B: SplitByteSlice> {
+    big_data_slices: Option<BigDataSlices<'h, B>>,
+    bytes: &'h [u8],
+    zeros: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<'h, B> LossyDataReader<'h, B>
+where
+    B: SplitByteSlice,
+{
+    fn new(data: &KeyValueData<'h, B>) -> Self {
+        match data {
+            KeyValueData::Small(data) => Self {
+                big_data_slices: None,
+                bytes: data,
+                zeros: 0,
+            },
+            KeyValueData::Big(iter) => Self {
+                big_data_slices: Some(iter.clone()),
+                bytes: &[],
+                zeros: 0,
+            },
+        }
+    }
+
+    /// Fills `buf` with the next bytes and returns how many have been filled.
+    /// This is only less than `buf.len()` when the end of the data has been reached.
+    fn fill(&mut self, buf: &mut [u8]) -> usize {
+        let mut filled = 0;
+
+        while filled < buf.len() {
+            let remaining = buf.len() - filled;
+
+            if !self.bytes.is_empty() {
+                let len = cmp::min(self.bytes.len(), remaining);
+                buf[filled..filled + len].copy_from_slice(&self.bytes[..len]);
+                self.bytes = &self.bytes[len..];
+                filled += len;
+            } else if self.zeros > 0 {
+                let len = cmp::min(self.zeros, remaining);
+                buf[filled..filled + len].fill(0);
+                self.zeros -= len;
+                filled += len;
+            } else {
+                let Some(iter) = self.big_data_slices.as_mut() else {
+                    break;
+                };
+
+                let segment_size = iter.next_segment_size();
+                match iter.next() {
+                    Some(Ok(slice_data)) => self.bytes = slice_data,
+                    Some(Err(_)) => self.zeros = segment_size,
+                    None => break,
+                }
+            }
+        }
+
+        filled
+    }
 }
 
+/// Name, data type, and data of a [`KeyValue`], returned by [`KeyNode::value_entries`].
+///
+/// [`KeyNode::value_entries`]: crate::key_node::KeyNode::value_entries
+pub type KeyValueEntry<'h, B> = (NtHiveNameString<'h>, KeyValueDataType, KeyValueData<'h, B>);
+
 /// Possible data types of the data belonging to a [`KeyValue`].
 #[derive(Clone, Copy, Debug, Eq, N, PartialEq)]
 #[repr(u32)]
@@ -90,18 +340,57 @@ pub enum KeyValueDataType {
     RegQWord = 0x0000_000b,
 }
 
+impl KeyValueDataType {
+    /// Returns `true` if this data type holds raw binary data.
+    ///
+    /// These are [`KeyValueDataType::RegNone`], [`KeyValueDataType::RegBinary`],
+    /// and all resource data types.
+    pub const fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            Self::RegNone
+                | Self::RegBinary
+                | Self::RegResourceList
+                | Self::RegFullResourceDescriptor
+                | Self::RegResourceRequirementsList
+        )
+    }
+
+    /// Returns `true` if this data type holds an integer.
+    ///
+    /// These are [`KeyValueDataType::RegDWord`], [`KeyValueDataType::RegDWordBigEndian`],
+    /// and [`KeyValueDataType::RegQWord`].
+    pub const fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Self::RegDWord | Self::RegDWordBigEndian | Self::RegQWord
+        )
+    }
+
+    /// Returns `true` if this data type holds one or more UTF-16LE strings.
+    ///
+    /// These are [`KeyValueDataType::RegSZ`], [`KeyValueDataType::RegExpandSZ`],
+    /// [`KeyValueDataType::RegMultiSZ`], and [`KeyValueDataType::RegLink`].
+    pub const fn is_string(&self) -> bool {
+        matches!(
+            self,
+            Self::RegSZ | Self::RegExpandSZ | Self::RegMultiSZ | Self::RegLink
+        )
+    }
+}
+
 /// On-Disk Structure of a Key Value header.
 #[allow(dead_code)]
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
-struct KeyValueHeader {
-    signature: [u8; 2],
-    name_length: U16<LittleEndian>,
-    data_size: U32<LittleEndian>,
-    data_offset: U32<LittleEndian>,
-    data_type: U32<LittleEndian>,
-    flags: U16<LittleEndian>,
-    spare: U16<LittleEndian>,
+#[repr(C, packed)]
+pub(crate) struct KeyValueHeader {
+    pub(crate) signature: [u8; 2],
+    pub(crate) name_length: U16<LittleEndian>,
+    pub(crate) data_size: U32<LittleEndian>,
+    pub(crate) data_offset: U32<LittleEndian>,
+    pub(crate) data_type: U32<LittleEndian>,
+    pub(crate) flags: U16<LittleEndian>,
+    pub(crate) spare: U16<LittleEndian>,
 }
 
 /// A single value that belongs to a [`KeyNode`].
@@ -110,7 +399,6 @@ struct KeyValueHeader {
 /// On-Disk Signature: `vk`
 ///
 /// [`KeyNode`]: crate::key_node::KeyNode
-#[derive(Clone)]
 pub struct KeyValue<'h, B: SplitByteSlice> {
     hive: &'h Hive<B>,
     header_range: Range<usize>,
@@ -124,6 +412,7 @@ where
     pub(crate) fn new(hive: &'h Hive<B>, cell_range: Range<usize>) -> Result<Self> {
         let header_range = byte_subrange(&cell_range, mem::size_of::<KeyValueHeader>())
             .ok_or_else(|| NtHiveError::InvalidHeaderSize {
+                context: "key value",
                 offset: hive.offset_of_data_offset(cell_range.start),
                 expected: mem::size_of::<KeyValueHeader>(),
                 actual: cell_range.len(),
@@ -140,10 +429,84 @@ where
         Ok(key_value)
     }
 
+    /// Returns the data offset of the cell holding this Key Value.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn cell_offset(&self) -> u32 {
+        (self.header_range.start - mem::size_of::<CellHeader>()) as u32
+    }
+
     fn header(&self) -> Ref<&[u8], KeyValueHeader> {
         Ref::from_bytes(&self.hive.data[self.header_range.clone()]).unwrap()
     }
 
+    /// Interprets the data as an array of little-endian [`u16`] integers.
+    ///
+    /// The data is borrowed from the hive if possible and copied otherwise
+    /// (e.g. when it is misaligned or split over Big Data segments).
+    #[cfg(feature = "alloc")]
+    pub fn as_u16_slice(&self) -> Result<Cow<'h, [u16]>> {
+        self.as_integer_slice(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Interprets the data as an array of little-endian [`u32`] integers.
+    ///
+    /// The data is borrowed from the hive if possible and copied otherwise
+    /// (e.g. when it is misaligned or split over Big Data segments).
+    #[cfg(feature = "alloc")]
+    pub fn as_u32_slice(&self) -> Result<Cow<'h, [u32]>> {
+        self.as_integer_slice(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn as_integer_slice<T>(&self, from_le_bytes: fn(&[u8]) -> T) -> Result<Cow<'h, [T]>>
+    where
+        T: Clone + FromBytes + Immutable,
+    {
+        let element_size = mem::size_of::<T>();
+        let data_size = self.data_size() as usize;
+        if data_size % element_size != 0 {
+            return Err(NtHiveError::InvalidSizeFieldAlignment {
+                context: "key value data",
+                offset: self.hive.offset_of_field(&self.header().data_size),
+                size: data_size,
+                expected_alignment: element_size,
+            });
+        }
+
+        let data = match self.data()? {
+            KeyValueData::Small(data) => {
+                // We can only borrow if the data is suitably aligned and the in-memory
+                // representation of `T` matches the on-disk one.
+                if cfg!(target_endian = "little") {
+                    if let Ok(slice) = <[T]>::ref_from_bytes(data) {
+                        return Ok(Cow::Borrowed(slice));
+                    }
+                }
+
+                Cow::Borrowed(data)
+            }
+            data @ KeyValueData::Big(_) => Cow::Owned(data.into_vec()?),
+        };
+
+        let integers = data.chunks_exact(element_size).map(from_le_bytes).collect();
+        Ok(Cow::Owned(integers))
+    }
+
+    /// Checks if this is a `REG_BINARY` Key Value
+    /// and returns the raw data bytes as [`KeyValueData`] in that case.
+    ///
+    /// Like [`KeyValue::data`], this doesn't copy any data.
+    pub fn binary_data(&self) -> Result<KeyValueData<'h, B>> {
+        // Ensure that this is a REG_BINARY data type.
+        match self.data_type()? {
+            KeyValueDataType::RegBinary => self.data(),
+            data_type => Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[KeyValueDataType::RegBinary],
+                actual: data_type,
+            }),
+        }
+    }
+
     /// Returns the raw data bytes as [`KeyValueData`].
     pub fn data(&self) -> Result<KeyValueData<'h, B>> {
         let header = self.header();
@@ -157,23 +520,31 @@ where
             // exceed the 4 bytes we have.
             if data_size > mem::size_of::<u32>() {
                 return Err(NtHiveError::InvalidSizeField {
+                    context: "key value",
                     offset: self.hive.offset_of_field(&header.data_size),
                     expected: mem::size_of::<u32>(),
                     actual: data_size,
                 });
             }
 
+            // The `data_offset` field is part of the already validated header,
+            // so this range is always valid (and empty for a data size of zero).
             let data_start = self.header_range.start + offset_of!(KeyValueHeader, data_offset);
             let data_end = data_start + data_size;
 
             Ok(KeyValueData::Small(&self.hive.data[data_start..data_end]))
-        } else if data_size <= BIG_DATA_SEGMENT_SIZE {
+        } else if data_size == 0 {
+            // Empty data needs no data cell, so `data_offset` may not reference one.
+            Ok(KeyValueData::Small(&[]))
+        } else if data_size <= BIG_DATA_SEGMENT_SIZE || !self.hive.supports(HiveFeature::BigData) {
             // The entire data is stored in a single cell referenced by `data_offset`.
+            // Hives predating Big Data also store bigger data that way.
             let cell_range = self
                 .hive
                 .cell_range_from_data_offset(header.data_offset.get())?;
             if cell_range.len() < data_size {
                 return Err(NtHiveError::InvalidDataSize {
+                    context: "key value data",
                     offset: self.hive.offset_of_data_offset(cell_range.start),
                     expected: data_size,
                     actual: cell_range.len(),
@@ -241,17 +612,11 @@ where
 
     /// Checks if this is a `REG_SZ` or `REG_EXPAND_SZ` Key Value
     /// and returns the data as a [`String`] in that case.
+    ///
+    /// Use [`encode_sz`] for the inverse operation.
     #[cfg(feature = "alloc")]
     pub fn string_data(&'h self) -> Result<String> {
-        match self.data_type()? {
-            KeyValueDataType::RegSZ | KeyValueDataType::RegExpandSZ => (),
-            data_type => {
-                return Err(NtHiveError::InvalidKeyValueDataType {
-                    expected: &[KeyValueDataType::RegSZ, KeyValueDataType::RegExpandSZ],
-                    actual: data_type,
-                });
-            }
-        }
+        self.validate_string_data_type()?;
 
         match self.data()? {
             KeyValueData::Small(data) => Self::utf16le_to_string_lossy(iter::once(Ok(data))),
@@ -259,15 +624,137 @@ where
         }
     }
 
+    /// Like [`KeyValue::string_data`], but removes a leading UTF-16 byte order mark (U+FEFF)
+    /// that some applications erroneously store.
+    ///
+    /// [`KeyValue::string_data`] keeps the data as stored.
+    /// A byte order mark of the wrong endianness is decoded as U+FFFE and left in place,
+    /// so it can be detected via `starts_with('\u{fffe}')`.
+    #[cfg(feature = "alloc")]
+    pub fn string_data_strip_bom(&'h self) -> Result<String> {
+        let mut string = self.string_data()?;
+
+        if string.starts_with('\u{feff}') {
+            string.drain(..'\u{feff}'.len_utf8());
+        }
+
+        Ok(string)
+    }
+
+    /// Like [`KeyValue::string_data`], but interprets the data as UTF-8 instead of UTF-16LE.
+    ///
+    /// Some third-party applications erroneously store UTF-8 strings in `REG_SZ` values.
+    /// Only use this if you know that a value is affected, as there is no reliable way to detect it.
+    /// Invalid UTF-8 sequences are replaced by U+FFFD, and the string ends at the first NUL byte.
+    #[cfg(feature = "alloc")]
+    pub fn string_data_utf8(&self) -> Result<String> {
+        self.validate_string_data_type()?;
+
+        let mut data = self.data()?.into_vec()?;
+        if let Some(nul_position) = data.iter().position(|byte| *byte == 0) {
+            data.truncate(nul_position);
+        }
+
+        let string = match String::from_utf8(data) {
+            Ok(string) => string,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        };
+
+        Ok(string)
+    }
+
+    /// Checks if this is a `REG_SZ`, `REG_EXPAND_SZ`, or `REG_MULTI_SZ` Key Value
+    /// and returns the data as a list of [`String`]s in that case.
+    ///
+    /// `REG_SZ` and `REG_EXPAND_SZ` data results in a single string, while `REG_MULTI_SZ` data
+    /// results in a string for each line.
+    /// This smooths over applications that store a string with the wrong one of these data types.
+    #[cfg(feature = "alloc")]
+    pub fn string_or_multi_string(&self) -> Result<Vec<String>> {
+        match self.data_type()? {
+            KeyValueDataType::RegSZ | KeyValueDataType::RegExpandSZ => {
+                let string = match self.data()? {
+                    KeyValueData::Small(data) => {
+                        Self::utf16le_to_string_lossy(iter::once(Ok(data)))?
+                    }
+                    KeyValueData::Big(iter) => Self::utf16le_to_string_lossy(iter)?,
+                };
+
+                Ok(vec![string])
+            }
+            KeyValueDataType::RegMultiSZ => self.multi_string_data()?.collect(),
+            data_type => Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[
+                    KeyValueDataType::RegSZ,
+                    KeyValueDataType::RegExpandSZ,
+                    KeyValueDataType::RegMultiSZ,
+                ],
+                actual: data_type,
+            }),
+        }
+    }
+
     /// Checks if this is a `REG_DWORD` or `REG_DWORD_BIG_ENDIAN` Key Value
     /// and returns the data as a [`u32`] in that case.
+    ///
+    /// Use [`KeyValue::dword_data_raw`] to retrieve the bytes in their stored byte order.
     pub fn dword_data(&self) -> Result<u32> {
+        match self.dword_data_raw()? {
+            (KeyValueDataType::RegDWordBigEndian, bytes) => Ok(u32::from_be_bytes(bytes)),
+            (_, bytes) => Ok(u32::from_le_bytes(bytes)),
+        }
+    }
+
+    /// Like [`KeyValue::dword_data`], but also accepts data shorter than 4 bytes
+    /// and zero-extends it to a [`u32`].
+    ///
+    /// Some writers store a `REG_DWORD` with a declared size of less than 4 bytes directly
+    /// in the `data_offset` field.
+    /// Windows returns such data as-is, so this function interprets the available bytes
+    /// in the byte order of the data type, as if the missing high-order bytes were zero.
+    /// Data longer than 4 bytes is still rejected.
+    pub fn dword_data_lenient(&self) -> Result<u32> {
+        let data_type = self.data_type()?;
+        if !matches!(
+            data_type,
+            KeyValueDataType::RegDWord | KeyValueDataType::RegDWordBigEndian
+        ) {
+            return Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[
+                    KeyValueDataType::RegDWord,
+                    KeyValueDataType::RegDWordBigEndian,
+                ],
+                actual: data_type,
+            });
+        }
+
+        let data = match self.data()? {
+            KeyValueData::Small(data) if data.len() <= mem::size_of::<u32>() => data,
+            _ => return self.dword_data(),
+        };
+
+        let mut bytes = [0u8; mem::size_of::<u32>()];
+        if data_type == KeyValueDataType::RegDWord {
+            bytes[..data.len()].copy_from_slice(data);
+            Ok(u32::from_le_bytes(bytes))
+        } else {
+            bytes[mem::size_of::<u32>() - data.len()..].copy_from_slice(data);
+            Ok(u32::from_be_bytes(bytes))
+        }
+    }
+
+    /// Checks if this is a `REG_DWORD` or `REG_DWORD_BIG_ENDIAN` Key Value
+    /// and returns the data type along with the 4 data bytes as stored, without converting them.
+    ///
+    /// This preserves the byte order of the stored data, which [`KeyValue::dword_data`] normalizes.
+    pub fn dword_data_raw(&self) -> Result<(KeyValueDataType, [u8; 4])> {
         // DWORD data never needs a Big Data structure.
         if let KeyValueData::Small(data) = self.data()? {
             // DWORD data must be exactly 4 bytes long.
             if data.len() != mem::size_of::<u32>() {
                 return Err(NtHiveError::InvalidDataSize {
-                    offset: self.hive.offset_of_field(&data),
+                    context: "key value data",
+                    offset: self.hive.offset_of_field(data),
                     expected: mem::size_of::<u32>(),
                     actual: data.len(),
                 });
@@ -275,9 +762,8 @@ where
 
             // Ensure that this is a REG_DWORD or REG_DWORD_BIG_ENDIAN data type.
             match self.data_type()? {
-                KeyValueDataType::RegDWord => Ok(u32::from_le_bytes(data.try_into().unwrap())),
-                KeyValueDataType::RegDWordBigEndian => {
-                    Ok(u32::from_be_bytes(data.try_into().unwrap()))
+                data_type @ (KeyValueDataType::RegDWord | KeyValueDataType::RegDWordBigEndian) => {
+                    Ok((data_type, data.try_into().unwrap()))
                 }
                 data_type => Err(NtHiveError::InvalidKeyValueDataType {
                     expected: &[
@@ -291,6 +777,7 @@ where
             // We got a Big Data structure and this can only happen if the data
             // is much longer than a single DWORD.
             Err(NtHiveError::InvalidDataSize {
+                context: "key value data",
                 offset: self
                     .hive
                     .offset_of_data_offset(self.header().data_offset.get() as usize),
@@ -300,8 +787,35 @@ where
         }
     }
 
+    /// Checks if this is a `REG_LINK` Key Value
+    /// and returns the absolute registry path of the link target in that case.
+    ///
+    /// Symbolic links are usually stored in a value named `SymbolicLinkValue`, with a path like
+    /// `\Registry\Machine\System\ControlSet001`.
+    /// The data is decoded like [`KeyValue::string_data`] does.
+    #[cfg(feature = "alloc")]
+    pub fn link_target(&self) -> Result<String> {
+        // Ensure that this is a REG_LINK data type.
+        match self.data_type()? {
+            KeyValueDataType::RegLink => (),
+            data_type => {
+                return Err(NtHiveError::InvalidKeyValueDataType {
+                    expected: &[KeyValueDataType::RegLink],
+                    actual: data_type,
+                });
+            }
+        }
+
+        match self.data()? {
+            KeyValueData::Small(data) => Self::utf16le_to_string_lossy(iter::once(Ok(data))),
+            KeyValueData::Big(iter) => Self::utf16le_to_string_lossy(iter),
+        }
+    }
+
     /// Checks if this is a `REG_MULTI_SZ` Key Value
     /// and returns an iterator over [`String`]s for each line in that case.
+    ///
+    /// Use [`encode_multi_sz`] for the inverse operation.
     #[cfg(feature = "alloc")]
     pub fn multi_string_data(&self) -> Result<RegMultiSZStrings<'h, B>> {
         // Ensure that this is a REG_MULTI_SZ data type.
@@ -321,6 +835,56 @@ where
         }
     }
 
+    /// Checks if this is a `REG_MULTI_SZ` Key Value
+    /// and returns an iterator over the UTF-16LE bytes of each line in that case.
+    ///
+    /// The lines are borrowed from the hive without any allocations, which is why this only works
+    /// for data stored in a single cell.
+    /// `None` is returned for data requiring a Big Data structure.
+    /// Use [`KeyValue::multi_string_data`] to get owned lines in every case.
+    ///
+    /// Like [`KeyValue::multi_string_data`], this stops at the first empty line.
+    pub fn multi_string_slices_small(&self) -> Result<Option<impl Iterator<Item = &'h [u8]>>> {
+        // Ensure that this is a REG_MULTI_SZ data type.
+        match self.data_type()? {
+            KeyValueDataType::RegMultiSZ => (),
+            data_type => {
+                return Err(NtHiveError::InvalidKeyValueDataType {
+                    expected: &[KeyValueDataType::RegMultiSZ],
+                    actual: data_type,
+                });
+            }
+        }
+
+        let data = match self.data()? {
+            KeyValueData::Small(data) => data,
+            KeyValueData::Big(_) => return Ok(None),
+        };
+
+        // Ignore a dangling last byte, which is no complete UTF-16 code unit.
+        let mut remaining = &data[..data.len() & !1];
+
+        let iter = iter::from_fn(move || {
+            // Lines are terminated by a NUL code unit, but the last one may lack it.
+            let line_len = remaining
+                .chunks_exact(2)
+                .position(|code_unit| code_unit == [0, 0])
+                .map_or(remaining.len(), |index| index * 2);
+            let line = &remaining[..line_len];
+            remaining = remaining.get(line_len + 2..).unwrap_or_default();
+
+            if line.is_empty() {
+                // An empty line marks the end of the data.
+                remaining = &[];
+                None
+            } else {
+                Some(line)
+            }
+        });
+
+        Ok(Some(iter))
+    }
+
     /// Checks if this is a `REG_QWORD` Key Value
     /// and returns the data as a [`u64`] in that case.
     pub fn qword_data(&self) -> Result<u64> {
@@ -329,7 +893,8 @@ where
             // QWORD data must be exactly 8 bytes long.
             if data.len() != mem::size_of::<u64>() {
                 return Err(NtHiveError::InvalidDataSize {
-                    offset: self.hive.offset_of_field(&data),
+                    context: "key value data",
+                    offset: self.hive.offset_of_field(data),
                     expected: mem::size_of::<u64>(),
                     actual: data.len(),
                 });
@@ -347,6 +912,7 @@ where
             // We got a Big Data structure and this can only happen if the data
             // is much longer than a single QWORD.
             Err(NtHiveError::InvalidDataSize {
+                context: "key value data",
                 offset: self
                     .hive
                     .offset_of_data_offset(self.header().data_offset.get() as usize),
@@ -356,6 +922,83 @@ where
         }
     }
 
+    /// Checks if this is a `REG_RESOURCE_LIST` Key Value
+    /// and returns the parsed `CM_RESOURCE_LIST` in that case.
+    ///
+    /// Resource lists requiring a Big Data structure are not supported.
+    pub fn resource_list(&self) -> Result<CmResourceList<'h>> {
+        // Ensure that this is a REG_RESOURCE_LIST data type.
+        match self.data_type()? {
+            KeyValueDataType::RegResourceList => (),
+            data_type => {
+                return Err(NtHiveError::InvalidKeyValueDataType {
+                    expected: &[KeyValueDataType::RegResourceList],
+                    actual: data_type,
+                });
+            }
+        }
+
+        match self.data()? {
+            KeyValueData::Small(data) => CmResourceList::new(self.hive, data),
+            KeyValueData::Big(_) => Err(NtHiveError::InvalidDataSize {
+                context: "key value data",
+                offset: self
+                    .hive
+                    .offset_of_data_offset(self.header().data_offset.get() as usize),
+                expected: BIG_DATA_SEGMENT_SIZE,
+                actual: self.data_size() as usize,
+            }),
+        }
+    }
+
+    /// Reads the raw data starting at byte `offset` into `buf` and returns the number of bytes read.
+    ///
+    /// Fewer than `buf.len()` bytes are read if the end of the data is reached,
+    /// and 0 is returned if `offset` is at or beyond the end.
+    /// For Big Data, only the segments covering the requested range are accessed.
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize> {
+        match self.data()? {
+            KeyValueData::Small(data) => {
+                let remaining = data.get(offset..).unwrap_or_default();
+                let bytes_to_read = cmp::min(remaining.len(), buf.len());
+                buf[..bytes_to_read].copy_from_slice(&remaining[..bytes_to_read]);
+
+                Ok(bytes_to_read)
+            }
+            KeyValueData::Big(mut iter) => {
+                // Every segment contains BIG_DATA_SEGMENT_SIZE bytes of data except for the last one,
+                // so we can directly skip to the segment containing `offset`.
+                let mut segment_offset = offset % BIG_DATA_SEGMENT_SIZE;
+                let mut slice_data = iter.nth(offset / BIG_DATA_SEGMENT_SIZE);
+                let mut bytes_read = 0;
+
+                while bytes_read < buf.len() {
+                    let Some(data) = slice_data else {
+                        break;
+                    };
+
+                    let remaining = data?.get(segment_offset..).unwrap_or_default();
+                    let bytes_to_read = cmp::min(remaining.len(), buf.len() - bytes_read);
+                    buf[bytes_read..bytes_read + bytes_to_read]
+                        .copy_from_slice(&remaining[..bytes_to_read]);
+                    bytes_read += bytes_to_read;
+
+                    segment_offset = 0;
+                    slice_data = iter.next();
+                }
+
+                Ok(bytes_read)
+            }
+        }
+    }
+
+    /// Returns `true` if this Key Value has no data.
+    ///
+    /// This only checks [`KeyValue::data_size`] and doesn't resolve the data cell.
+    pub fn data_is_empty(&self) -> bool {
+        self.data_size() == 0
+    }
+
     /// Returns the size of the raw data.
     pub fn data_size(&self) -> u32 {
         let header = self.header();
@@ -375,7 +1018,35 @@ where
         })
     }
 
+    /// Returns the data type of this Key Value (or `None` if it is unknown) along with the raw
+    /// data type code.
+    ///
+    /// Unlike [`KeyValue::data_type`], this never fails and keeps the code for logging
+    /// unknown data types.
+    pub fn data_type_full(&self) -> (Option<KeyValueDataType>, u32) {
+        let data_type_code = self.data_type_raw();
+        (KeyValueDataType::n(data_type_code), data_type_code)
+    }
+
+    /// Returns the raw data type code of this Key Value, without mapping it to a
+    /// [`KeyValueDataType`].
+    ///
+    /// This allows inspecting vendor-specific data types, whose data can still be retrieved
+    /// via [`KeyValue::data`].
+    pub fn data_type_raw(&self) -> u32 {
+        self.header().data_type.get()
+    }
+
+    /// Returns `true` if this is the "(Default)" value of a key, i.e. it has an empty name.
+    ///
+    /// This only checks the name length and therefore never fails.
+    pub fn is_default_value(&self) -> bool {
+        self.header().name_length.get() == 0
+    }
+
     /// Returns the name of this Key Value.
+    ///
+    /// The "(Default)" value of a key has an empty name, see [`KeyValue::is_default_value`].
     pub fn name(&self) -> Result<NtHiveNameString<'h>> {
         let header = self.header();
         let flags = KeyValueFlags::from_bits_truncate(header.flags.get());
@@ -383,6 +1054,7 @@ where
 
         let name_range = byte_subrange(&self.data_range, name_length).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "key value",
                 offset: self.hive.offset_of_field(&header.name_length),
                 expected: name_length,
                 actual: self.data_range.len(),
@@ -397,6 +1069,34 @@ where
         }
     }
 
+    /// Returns `true` if the `VALUE_COMP_NAME` flag of this Key Value says that its name is stored
+    /// "compressed" as Latin1, or `false` if it is stored as UTF-16LE.
+    pub fn name_is_compressed(&self) -> bool {
+        let flags = KeyValueFlags::from_bits_truncate(self.header().flags.get());
+        flags.contains(KeyValueFlags::VALUE_COMP_NAME)
+    }
+
+    /// Returns the name of this Key Value, recovering from a UTF-16LE name with an odd byte length.
+    ///
+    /// In that case, the dangling last byte is dropped and the returned flag is `true`.
+    pub fn name_lossy(&self) -> Result<(NtHiveNameString<'h>, bool)> {
+        let name = self.name()?;
+        Ok(name.without_dangling_byte())
+    }
+
+    /// Returns a copy of this [`KeyValue`] that borrows the hive only for the lifetime of `self`.
+    ///
+    /// This is as cheap as [`Clone::clone`] and eases passing a Key Value to helper functions
+    /// that don't need the full hive lifetime.
+    pub fn reborrow(&self) -> KeyValue<'_, B> {
+        self.clone()
+    }
+
+    /// Returns the value of the 2-byte `Spare` field following the flags of this Key Value.
+    pub fn spare(&self) -> u16 {
+        self.header().spare.get()
+    }
+
     fn validate_signature(&self) -> Result<()> {
         let header = self.header();
         let signature = &header.signature;
@@ -406,27 +1106,206 @@ where
             Ok(())
         } else {
             Err(NtHiveError::InvalidTwoByteSignature {
+                context: "key value",
                 offset: self.hive.offset_of_field(signature),
                 expected: expected_signature,
                 actual: *signature,
             })
         }
     }
+
+    #[cfg(feature = "alloc")]
+    fn validate_string_data_type(&self) -> Result<()> {
+        match self.data_type()? {
+            KeyValueDataType::RegSZ | KeyValueDataType::RegExpandSZ => Ok(()),
+            data_type => Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[KeyValueDataType::RegSZ, KeyValueDataType::RegExpandSZ],
+                actual: data_type,
+            }),
+        }
+    }
 }
 
-impl<B> PartialEq for KeyValue<'_, B>
+impl<B> Clone for KeyValue<'_, B>
 where
     B: SplitByteSlice,
 {
-    fn eq(&self, other: &Self) -> bool {
-        ptr::eq(self.hive, other.hive)
-            && self.header_range == other.header_range
-            && self.data_range == other.data_range
+    fn clone(&self) -> Self {
+        Self {
+            hive: self.hive,
+            header_range: self.header_range.clone(),
+            data_range: self.data_range.clone(),
+        }
+    }
+}
+
+impl<B> fmt::Debug for KeyValue<'_, B>
+where
+    B: SplitByteSlice,
+{
+    /// Formats the name, the data type, and a short preview of the data.
+    /// Only the first few bytes of the data are accessed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("KeyValue");
+
+        match self.name() {
+            Ok(name) => debug_struct.field("name", &name),
+            Err(e) => debug_struct.field("name", &Err::<(), _>(e)),
+        };
+
+        match self.data_type_full() {
+            (Some(data_type), _) => debug_struct.field("data_type", &data_type),
+            (None, data_type_code) => {
+                debug_struct.field("data_type", &format_args!("{data_type_code:#010x}"))
+            }
+        };
+
+        debug_struct
+            .field("data", &KeyValueDataPreview(self))
+            .finish()
+    }
+}
+
+/// Maximum number of data bytes shown by the [`fmt::Debug`] implementation of [`KeyValue`].
+const DEBUG_PREVIEW_SIZE: usize = 32;
+
+/// Helper for the [`fmt::Debug`] implementation of [`KeyValue`].
+struct KeyValueDataPreview<'a, 'h, B: SplitByteSlice>(&'a KeyValue<'h, B>);
+
+impl<B> KeyValueDataPreview<'_, '_, B>
+where
+    B: SplitByteSlice,
+{
+    fn fmt_bytes(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; DEBUG_PREVIEW_SIZE];
+        let bytes_read = match self.0.read_at(0, &mut buf) {
+            Ok(bytes_read) => bytes_read,
+            Err(e) => return write!(f, "Err({e:?})"),
+        };
+
+        f.write_str("[")?;
+        for (i, byte) in buf[..bytes_read].iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        f.write_str("]")?;
+
+        self.fmt_ellipsis(f, bytes_read)
+    }
+
+    fn fmt_ellipsis(&self, f: &mut fmt::Formatter<'_>, bytes_shown: usize) -> fmt::Result {
+        if (self.0.data_size() as usize) > bytes_shown {
+            f.write_str("...")?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt_string(&self, f: &mut fmt::Formatter<'_>, stop_at_nul: bool) -> fmt::Result {
+        let mut buf = [0u8; DEBUG_PREVIEW_SIZE];
+        let bytes_read = match self.0.read_at(0, &mut buf) {
+            Ok(bytes_read) => bytes_read,
+            Err(e) => return write!(f, "Err({e:?})"),
+        };
+
+        let u16_iter = buf[..bytes_read]
+            .chunks_exact(2)
+            .map(|two_bytes| u16::from_le_bytes(two_bytes.try_into().unwrap()));
+        let char_iter =
+            char::decode_utf16(u16_iter).map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER));
+
+        f.write_str("\"")?;
+        for single_char in char_iter {
+            if stop_at_nul && single_char == '\0' {
+                return f.write_str("\"");
+            }
+
+            write!(f, "{}", single_char.escape_debug())?;
+        }
+        f.write_str("\"")?;
+
+        self.fmt_ellipsis(f, bytes_read)
+    }
+}
+
+impl<B> fmt::Debug for KeyValueDataPreview<'_, '_, B>
+where
+    B: SplitByteSlice,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key_value = self.0;
+
+        match key_value.data_type_full().0 {
+            Some(KeyValueDataType::RegSZ)
+            | Some(KeyValueDataType::RegExpandSZ)
+            | Some(KeyValueDataType::RegLink) => self.fmt_string(f, true),
+            Some(KeyValueDataType::RegMultiSZ) => self.fmt_string(f, false),
+            Some(KeyValueDataType::RegDWord) | Some(KeyValueDataType::RegDWordBigEndian) => {
+                match key_value.dword_data() {
+                    Ok(dword) => write!(f, "{dword}"),
+                    Err(_) => self.fmt_bytes(f),
+                }
+            }
+            Some(KeyValueDataType::RegQWord) => match key_value.qword_data() {
+                Ok(qword) => write!(f, "{qword}"),
+                Err(_) => self.fmt_bytes(f),
+            },
+            _ => self.fmt_bytes(f),
+        }
+    }
+}
+
+impl<B> PartialEq for KeyValue<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.hive, other.hive)
+            && self.header_range == other.header_range
+            && self.data_range == other.data_range
     }
 }
 
 impl<B> Eq for KeyValue<'_, B> where B: SplitByteSlice {}
 
+/// Encodes the given lines as `REG_MULTI_SZ` data.
+///
+/// Every line is encoded as UTF-16LE and terminated by a NUL character.
+/// Another NUL character terminates the entire data.
+/// This is the inverse of [`KeyValue::multi_string_data`].
+///
+/// Note that empty lines cannot be represented, because an empty line marks the end of the data.
+#[cfg(feature = "alloc")]
+pub fn encode_multi_sz<I, S>(lines: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut data = Vec::new();
+
+    for line in lines {
+        let code_units = line.as_ref().encode_utf16().chain(iter::once(0));
+        data.extend(code_units.flat_map(u16::to_le_bytes));
+    }
+
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data
+}
+
+/// Encodes the given string as `REG_SZ` or `REG_EXPAND_SZ` data.
+///
+/// The string is encoded as UTF-16LE and terminated by a NUL character.
+/// This is the inverse of [`KeyValue::string_data`].
+#[cfg(feature = "alloc")]
+pub fn encode_sz(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
 #[cfg(feature = "alloc")]
 type RegMultiSZCharIter<'h> = Map<
     DecodeUtf16<Map<ChunksExact<'h, u8>, fn(&'h [u8]) -> u16>>,
@@ -542,6 +1421,78 @@ impl<'h, B> FusedIterator for RegMultiSZStrings<'h, B> where B: SplitByteSlice +
 mod tests {
     use crate::*;
 
+    #[test]
+    fn test_binary_data() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        let key_value_data = key_value.binary_data().unwrap();
+        assert!(matches!(
+            key_value_data,
+            KeyValueData::Small(&[1, 2, 3, 4, 5])
+        ));
+
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert!(matches!(
+            key_value.binary_data(),
+            Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[KeyValueDataType::RegBinary],
+                actual: KeyValueDataType::RegDWord,
+            })
+        ));
+
+        // Big Data is returned as well.
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+        let key_value = key_node.value("C").unwrap().unwrap();
+        let key_value_data = key_value.binary_data().unwrap();
+        assert!(matches!(key_value_data, KeyValueData::Big(_)));
+        assert!(key_value_data == key_value.data().unwrap());
+    }
+
+    #[test]
+    fn test_copy_to_slice() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        let data = key_value.data().unwrap();
+        let mut buffer = [0u8; 5];
+        assert_eq!(data.copy_to_slice(&mut buffer).unwrap(), 5);
+        assert_eq!(buffer, [1, 2, 3, 4, 5]);
+
+        let mut buffer = [0u8; 4];
+        assert_eq!(
+            data.copy_to_slice(&mut buffer),
+            Err(NtHiveError::BufferTooSmall {
+                expected: 5,
+                actual: 4
+            })
+        );
+
+        // Big data is copied across all of its cells.
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        let data = key_value.data().unwrap();
+        assert!(matches!(data, KeyValueData::Big(_)));
+        let expected = data.clone().into_vec().unwrap();
+        let mut buffer = vec![0u8; expected.len() + 1];
+        assert_eq!(data.copy_to_slice(&mut buffer).unwrap(), expected.len());
+        assert_eq!(&buffer[..expected.len()], expected);
+
+        let mut buffer = vec![0u8; expected.len() - 1];
+        assert_eq!(
+            data.copy_to_slice(&mut buffer),
+            Err(NtHiveError::BufferTooSmall {
+                expected: expected.len(),
+                actual: expected.len() - 1
+            })
+        );
+    }
+
     #[test]
     fn test_data() {
         // Get Key Values of all data types we support and prove that we correctly
@@ -610,4 +1561,905 @@ mod tests {
         assert!(matches!(key_value_data, KeyValueData::Small(_)));
         assert_eq!(key_value_data.into_vec().unwrap(), vec![1, 2, 3, 4, 5]);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_data_eq_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<B: zerocopy::SplitByteSlice>(data: &KeyValueData<'_, B>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            data.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("C").unwrap().unwrap();
+        let big = key_value.data().unwrap();
+        assert!(matches!(big, KeyValueData::Big(_)));
+
+        // The same bytes stored in a single cell must compare and hash equal.
+        let bytes = big.clone().into_vec().unwrap();
+        let small = KeyValueData::Small(&bytes);
+        assert!(big == small);
+        assert!(small == big);
+        assert_eq!(hash_of(&big), hash_of(&small));
+
+        // A single differing byte or a different length makes them unequal.
+        let mut modified_bytes = bytes.clone();
+        *modified_bytes.last_mut().unwrap() ^= 0xff;
+        assert!(big != KeyValueData::Small(&modified_bytes));
+        assert!(big != KeyValueData::Small(&bytes[..bytes.len() - 1]));
+
+        let other = key_node.value("B").unwrap().unwrap().data().unwrap();
+        assert!(big != other);
+        assert!(big == big.clone());
+    }
+
+    #[test]
+    fn test_data_is_empty() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value("data-test", "empty", KeyValueDataType::RegBinary, &[])
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("empty").unwrap().unwrap();
+        assert!(key_value.data_is_empty());
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(!key_value.data_is_empty());
+    }
+
+    #[test]
+    fn test_data_type_categories() {
+        let binary_types = [
+            KeyValueDataType::RegNone,
+            KeyValueDataType::RegBinary,
+            KeyValueDataType::RegResourceList,
+            KeyValueDataType::RegFullResourceDescriptor,
+            KeyValueDataType::RegResourceRequirementsList,
+        ];
+        let integer_types = [
+            KeyValueDataType::RegDWord,
+            KeyValueDataType::RegDWordBigEndian,
+            KeyValueDataType::RegQWord,
+        ];
+        let string_types = [
+            KeyValueDataType::RegSZ,
+            KeyValueDataType::RegExpandSZ,
+            KeyValueDataType::RegMultiSZ,
+            KeyValueDataType::RegLink,
+        ];
+
+        // Every data type must belong to exactly one category.
+        let mut count = 0;
+        for raw_data_type in 0.. {
+            let Some(data_type) = KeyValueDataType::n(raw_data_type) else {
+                break;
+            };
+            count += 1;
+
+            assert_eq!(data_type.is_binary(), binary_types.contains(&data_type));
+            assert_eq!(data_type.is_integer(), integer_types.contains(&data_type));
+            assert_eq!(data_type.is_string(), string_types.contains(&data_type));
+        }
+
+        assert_eq!(
+            count,
+            binary_types.len() + integer_types.len() + string_types.len()
+        );
+    }
+
+    #[test]
+    fn test_data_type_full() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(
+            key_value.data_type_full(),
+            (Some(KeyValueDataType::RegDWord), 4)
+        );
+        assert_eq!(key_value.data_type_raw(), 4);
+
+        // Patch in an unknown data type code.
+        let data_type_offset = 4096
+            + key_value.cell_offset() as usize
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyValueHeader, data_type);
+        let mut patched = testhive.clone();
+        patched[data_type_offset..data_type_offset + 4].copy_from_slice(&0x1234u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.data_type_full(), (None, 0x1234));
+        assert_eq!(key_value.data_type_raw(), 0x1234);
+        assert!(key_value.data_type().is_err());
+
+        // The data of an unknown data type can still be read.
+        let data = key_value.data().unwrap().into_vec().unwrap();
+        assert_eq!(data, 42u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_data_without_big_data() {
+        // Turn our testhive into a version 1.3 hive, which predates Big Data.
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let minor_version_offset = memoffset::offset_of!(BaseBlock, minor_version);
+        testhive[minor_version_offset..minor_version_offset + 4]
+            .copy_from_slice(&(HiveMinorVersion::WindowsNT4 as u32).to_le_bytes());
+        crate::helpers::tests::fix_checksum(&mut testhive);
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // Such hives store data of any size in a single cell.
+        let data = (0..BIG_DATA_SEGMENT_SIZE * 2)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let patched = HivePatch::new()
+            .set_value("", "big", KeyValueDataType::RegBinary, &data)
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        assert!(!hive.supports(HiveFeature::BigData));
+
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_value = root_key_node.value("big").unwrap().unwrap();
+        let key_value_data = key_value.data().unwrap();
+        assert!(matches!(key_value_data, KeyValueData::Small(_)));
+        assert_eq!(key_value_data.into_vec().unwrap(), data);
+    }
+
+    #[test]
+    fn test_debug() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        let debug = format!("{key_value:?}");
+        assert!(debug.contains("42"));
+        assert!(debug.contains("RegDWord"));
+        assert_eq!(
+            debug,
+            r#"KeyValue { name: Latin1("dword"), data_type: RegDWord, data: 42 }"#
+        );
+
+        let key_value = key_node
+            .value("reg-sz-with-terminating-nul")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            format!("{key_value:?}"),
+            r#"KeyValue { name: Latin1("reg-sz-with-terminating-nul"), data_type: RegSZ, data: "sz-test" }"#
+        );
+
+        // Long data is cut off.
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        assert!(format!("{key_value:?}").ends_with(r#"data: "0123456789012345"... }"#));
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(format!("{key_value:?}").contains("data_type: RegBinary, data: ["));
+    }
+
+    #[test]
+    fn test_dword_data_lenient() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "short-dword",
+                KeyValueDataType::RegDWord,
+                &[0x34, 0x12],
+            )
+            .set_value(
+                "data-test",
+                "short-dword-be",
+                KeyValueDataType::RegDWordBigEndian,
+                &[0x12, 0x34],
+            )
+            .set_value(
+                "data-test",
+                "long-dword",
+                KeyValueDataType::RegDWord,
+                &[1; 6],
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        // The 2-byte data is stored inline and zero-extended.
+        let key_value = key_node.value("short-dword").unwrap().unwrap();
+        assert_eq!(key_value.data_size(), 2);
+        assert!(key_value.dword_data().is_err());
+        assert_eq!(key_value.dword_data_lenient().unwrap(), 0x1234);
+
+        let key_value = key_node.value("short-dword-be").unwrap().unwrap();
+        assert_eq!(key_value.dword_data_lenient().unwrap(), 0x1234);
+
+        // Valid DWORDs are read just like `dword_data` does.
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(
+            key_value.dword_data_lenient().unwrap(),
+            key_value.dword_data().unwrap()
+        );
+
+        // Longer data and other data types are still rejected.
+        let key_value = key_node.value("long-dword").unwrap().unwrap();
+        assert!(matches!(
+            key_value.dword_data_lenient(),
+            Err(NtHiveError::InvalidDataSize { expected: 4, .. })
+        ));
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(matches!(
+            key_value.dword_data_lenient(),
+            Err(NtHiveError::InvalidKeyValueDataType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dword_data_raw() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(
+            key_value.dword_data_raw().unwrap(),
+            (KeyValueDataType::RegDWord, [42, 0, 0, 0])
+        );
+
+        // The bytes are returned as stored, without swapping them.
+        let key_value = key_node.value("dword-big-endian").unwrap().unwrap();
+        assert_eq!(
+            key_value.dword_data_raw().unwrap(),
+            (KeyValueDataType::RegDWordBigEndian, [42, 0, 0, 0])
+        );
+
+        let key_value = key_node.value("qword").unwrap().unwrap();
+        assert!(matches!(
+            key_value.dword_data_raw(),
+            Err(NtHiveError::InvalidDataSize { expected: 4, .. })
+        ));
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(key_value.dword_data_raw().is_err());
+    }
+
+    #[test]
+    fn test_empty_data() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let mut patched = HivePatch::new()
+            .set_value("data-test", "empty-sz", KeyValueDataType::RegSZ, &[])
+            .set_value(
+                "data-test",
+                "empty-binary",
+                KeyValueDataType::RegBinary,
+                &[],
+            )
+            .apply(&hive)
+            .unwrap();
+
+        let check_empty = |hive: &Hive<&[u8]>| {
+            let root_key_node = hive.root_key_node().unwrap();
+            let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+            let key_value = key_node.value("empty-sz").unwrap().unwrap();
+            assert_eq!(key_value.data_size(), 0);
+            assert_eq!(key_value.string_data().unwrap(), "");
+
+            let key_value = key_node.value("empty-binary").unwrap().unwrap();
+            assert_eq!(key_value.data_size(), 0);
+            assert!(key_value.data_is_empty());
+            assert_eq!(key_value.read_at(0, &mut [0; 4]).unwrap(), 0);
+            let key_value_data = key_value.data().unwrap();
+            assert!(matches!(key_value_data, KeyValueData::Small(&[])));
+            assert_eq!(key_value_data.copy_to_slice(&mut []).unwrap(), 0);
+            assert_eq!(key_value_data.into_vec().unwrap(), Vec::<u8>::new());
+        };
+
+        // HivePatch stores empty data inline.
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        check_empty(&hive);
+
+        // Windows may also store it without the inline bit and without any data cell.
+        let key_value_offsets = {
+            let root_key_node = hive.root_key_node().unwrap();
+            let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+            ["empty-sz", "empty-binary"]
+                .map(|name| key_node.value(name).unwrap().unwrap().cell_offset() as usize)
+        };
+
+        for key_value_offset in key_value_offsets {
+            let header_offset = 4096 + key_value_offset + core::mem::size_of::<i32>();
+            let data_size_offset =
+                header_offset + memoffset::offset_of!(super::KeyValueHeader, data_size);
+            let data_offset_offset =
+                header_offset + memoffset::offset_of!(super::KeyValueHeader, data_offset);
+            patched[data_size_offset..data_size_offset + 4].copy_from_slice(&0u32.to_le_bytes());
+            patched[data_offset_offset..data_offset_offset + 4]
+                .copy_from_slice(&u32::MAX.to_le_bytes());
+        }
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        check_empty(&hive);
+    }
+
+    #[test]
+    fn test_empty_name() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // The "(Default)" value of a key has a zero-length name.
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "",
+                KeyValueDataType::RegSZ,
+                &encode_sz("default"),
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("").unwrap().unwrap();
+        let name = key_value.name().unwrap();
+        assert!(name.is_empty());
+        assert_eq!(name, "");
+        assert!(key_value.is_default_value());
+        assert_eq!(key_value.string_data().unwrap(), "default");
+
+        // Other values still have their names.
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.name().unwrap(), "dword");
+        assert!(!key_value.is_default_value());
+    }
+
+    #[test]
+    fn test_encode_multi_sz() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        // Our encoding must match what Windows has written.
+        let lines = ["multi-sz-test", "line2"];
+        let data = encode_multi_sz(lines);
+        let key_value = key_node.value("reg-multi-sz").unwrap().unwrap();
+        assert_eq!(data, key_value.data().unwrap().into_vec().unwrap());
+
+        // Round-trip a line outside the Basic Multilingual Plane.
+        let lines = vec![String::from("\u{10410}"), String::from("äöü")];
+        let data = encode_multi_sz(&lines);
+        let strings = RegMultiSZStrings::<&[u8]>::small(&data)
+            .collect::<Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(strings, lines);
+
+        assert_eq!(encode_multi_sz(Vec::<&str>::new()), [0, 0]);
+    }
+
+    #[test]
+    fn test_encode_sz() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // Our encoding must match what Windows has written.
+        let data = encode_sz("sz-test");
+        let key_value = hive
+            .root_key_node()
+            .unwrap()
+            .subpath("data-test")
+            .unwrap()
+            .unwrap()
+            .value("reg-sz-with-terminating-nul")
+            .unwrap()
+            .unwrap();
+        assert_eq!(data, key_value.data().unwrap().into_vec().unwrap());
+
+        // Round-trip a string through a new Key Value.
+        let string = "Ünïcödé \u{10410}";
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "encoded",
+                KeyValueDataType::RegSZ,
+                &encode_sz(string),
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("encoded").unwrap().unwrap();
+        assert_eq!(key_value.string_data().unwrap(), string);
+    }
+
+    #[test]
+    fn test_inline_data_size() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let key_value_offset = {
+            let root_key_node = hive.root_key_node().unwrap();
+            let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+            let key_value = key_node.value("dword").unwrap().unwrap();
+            key_value.cell_offset() as usize
+        };
+        let data_size_offset = 4096
+            + key_value_offset
+            + core::mem::size_of::<i32>()
+            + memoffset::offset_of!(super::KeyValueHeader, data_size);
+
+        let open_patched = |data_size: u32| {
+            let mut patched = testhive.clone();
+            patched[data_size_offset..data_size_offset + 4]
+                .copy_from_slice(&data_size.to_le_bytes());
+            patched
+        };
+
+        // The inline bit with a length of zero results in empty data.
+        let patched = open_patched(DATA_STORED_IN_DATA_OFFSET);
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.data_size(), 0);
+        assert!(matches!(
+            key_value.data().unwrap(),
+            KeyValueData::Small(&[])
+        ));
+
+        // Inline data can't be longer than the `data_offset` field.
+        let patched = open_patched(DATA_STORED_IN_DATA_OFFSET | 5);
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.data_size(), 5);
+        assert_eq!(
+            key_value.data().err(),
+            Some(NtHiveError::InvalidSizeField {
+                context: "key value",
+                offset: data_size_offset,
+                expected: 4,
+                actual: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_integer_data_errors() {
+        // Store integers with invalid lengths inline as well as in a separately referenced cell.
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "short-dword",
+                KeyValueDataType::RegDWord,
+                &[1, 2],
+            )
+            .set_value(
+                "data-test",
+                "long-dword",
+                KeyValueDataType::RegDWord,
+                &[1; 6],
+            )
+            .set_value(
+                "data-test",
+                "short-qword",
+                KeyValueDataType::RegQWord,
+                &[1; 6],
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        // The reported offset must point to the actual data in the hive.
+        let check_error = |name: &str, result: Result<()>, expected: usize| {
+            let key_value = key_node.value(name).unwrap().unwrap();
+            let KeyValueData::Small(data) = key_value.data().unwrap() else {
+                panic!("Expected small data for {name}");
+            };
+            let data_offset = data.as_ptr() as usize - patched.as_ptr() as usize;
+
+            assert_eq!(
+                result,
+                Err(NtHiveError::InvalidDataSize {
+                    context: "key value data",
+                    offset: data_offset,
+                    expected,
+                    actual: data.len(),
+                })
+            );
+        };
+
+        let key_value = key_node.value("short-dword").unwrap().unwrap();
+        check_error("short-dword", key_value.dword_data().map(|_| ()), 4);
+
+        let key_value = key_node.value("long-dword").unwrap().unwrap();
+        check_error("long-dword", key_value.dword_data().map(|_| ()), 4);
+
+        let key_value = key_node.value("short-qword").unwrap().unwrap();
+        check_error("short-qword", key_value.qword_data().map(|_| ()), 8);
+
+        // A valid QWORD is always stored in a separately referenced cell.
+        let key_value = key_node.value("qword").unwrap().unwrap();
+        assert!(key_value.data_size() > 4);
+        assert_eq!(key_value.qword_data().unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_integer_slices() {
+        use alloc::borrow::Cow;
+
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let integers = [1u32, 2, 0xdead_beef];
+        let integer_bytes = integers
+            .iter()
+            .flat_map(|integer| integer.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "u32-array",
+                KeyValueDataType::RegBinary,
+                &integer_bytes,
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("u32-array").unwrap().unwrap();
+        assert_eq!(key_value.as_u32_slice().unwrap().as_ref(), integers);
+        assert_eq!(
+            key_value.as_u16_slice().unwrap().as_ref(),
+            [1, 0, 2, 0, 0xbeef, 0xdead]
+        );
+
+        // 5 bytes are neither a multiple of 2 nor 4.
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(matches!(
+            key_value.as_u16_slice(),
+            Err(NtHiveError::InvalidSizeFieldAlignment { .. })
+        ));
+
+        // Big Data is always copied.
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        let slice = key_value.as_u16_slice().unwrap();
+        assert!(matches!(slice, Cow::Owned(_)));
+        assert_eq!(slice.len(), 8213);
+        assert_eq!(
+            slice[..3],
+            [u16::from(b'0'), u16::from(b'1'), u16::from(b'2')]
+        );
+
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+        let key_value = key_node.value("B").unwrap().unwrap();
+        let slice = key_value.as_u32_slice().unwrap();
+        assert_eq!(slice.len(), 16344 / 4);
+        assert!(slice.iter().all(|integer| *integer == 0x4242_4242));
+    }
+
+    #[test]
+    fn test_into_vec_lossy() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let expected = (0..40000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        let mut patched = HivePatch::new()
+            .set_value("data-test", "big", KeyValueDataType::RegBinary, &expected)
+            .apply(&hive)
+            .unwrap();
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("big").unwrap().unwrap();
+        let key_value_data = key_value.data().unwrap();
+        assert_eq!(
+            key_value_data.clone().into_vec_lossy(),
+            (expected.clone(), 0)
+        );
+        let KeyValueData::Big(iter) = key_value_data else {
+            panic!("Expected Big Data");
+        };
+        assert_eq!(iter.count(), 3);
+
+        // Let the middle segment point beyond the end of the hive.
+        let read_u32 = |bytes: &[u8], offset: usize| {
+            u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+        };
+        let cell_data_offset = |data_offset: usize| 4096 + data_offset + 4;
+        let big_data_offset = read_u32(
+            &patched,
+            cell_data_offset(key_value.cell_offset() as usize)
+                + memoffset::offset_of!(super::KeyValueHeader, data_offset),
+        );
+        let segment_list_offset = read_u32(
+            &patched,
+            cell_data_offset(big_data_offset)
+                + memoffset::offset_of!(crate::big_data::BigDataHeader, segment_list_offset),
+        );
+        let middle_segment_offset = cell_data_offset(segment_list_offset) + 4;
+        patched[middle_segment_offset..middle_segment_offset + 4]
+            .copy_from_slice(&0x7fff_fff8u32.to_le_bytes());
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("big").unwrap().unwrap();
+        assert!(key_value.data().unwrap().into_vec().is_err());
+
+        let (data, failed_segments) = key_value.data().unwrap().into_vec_lossy();
+        assert_eq!(failed_segments, 1);
+        assert_eq!(data.len(), expected.len());
+        assert_eq!(data[..16344], expected[..16344]);
+        assert!(data[16344..2 * 16344].iter().all(|byte| *byte == 0));
+        assert_eq!(data[2 * 16344..], expected[2 * 16344..]);
+    }
+
+    #[test]
+    fn test_link_target() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        // Symbolic links are stored without a terminating NUL character.
+        let target = "\\Registry\\Machine\\System\\ControlSet001";
+        let data = target
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let patched = HivePatch::new()
+            .create_key("link")
+            .set_value(
+                "link",
+                "SymbolicLinkValue",
+                KeyValueDataType::RegLink,
+                &data,
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        let key_node = root_key_node.subkey("link").unwrap().unwrap();
+        let key_value = key_node.value("SymbolicLinkValue").unwrap().unwrap();
+        assert_eq!(key_value.link_target().unwrap(), target);
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("reg-sz").unwrap().unwrap();
+        assert!(matches!(
+            key_value.link_target(),
+            Err(NtHiveError::InvalidKeyValueDataType {
+                expected: &[KeyValueDataType::RegLink],
+                actual: KeyValueDataType::RegSZ,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_multi_string_slices_small() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("reg-multi-sz").unwrap().unwrap();
+        let mut iter = key_value.multi_string_slices_small().unwrap().unwrap();
+        let line = iter.next().unwrap();
+        assert_eq!(line.len(), "multi-sz-test".len() * 2);
+        assert_eq!(NtHiveNameString::from_utf16le(line), "multi-sz-test");
+        assert_eq!(
+            NtHiveNameString::from_utf16le(iter.next().unwrap()),
+            "line2"
+        );
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        // The lines are borrowed straight from the hive data.
+        let KeyValueData::Small(data) = key_value.data().unwrap() else {
+            panic!("reg-multi-sz should be small");
+        };
+        let first_line = key_value
+            .multi_string_slices_small()
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(first_line.as_ptr(), data.as_ptr());
+
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        assert!(key_value.multi_string_slices_small().unwrap().is_none());
+
+        let key_value = key_node.value("reg-sz").unwrap().unwrap();
+        assert!(matches!(
+            key_value.multi_string_slices_small(),
+            Err(NtHiveError::InvalidKeyValueDataType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_at() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        let mut buffer = [0u8; 4];
+        assert_eq!(key_value.read_at(2, &mut buffer).unwrap(), 3);
+        assert_eq!(buffer[..3], [3, 4, 5]);
+        assert_eq!(key_value.read_at(5, &mut buffer).unwrap(), 0);
+        assert_eq!(key_value.read_at(usize::MAX, &mut buffer).unwrap(), 0);
+
+        // Read a window of the Big Data value crossing the boundary between its first two segments.
+        let key_value = key_node.value("reg-multi-sz-big").unwrap().unwrap();
+        let expected = key_value.data().unwrap().into_vec().unwrap();
+        let mut buffer = [0u8; 100];
+        assert_eq!(key_value.read_at(16300, &mut buffer).unwrap(), 100);
+        assert_eq!(buffer, expected[16300..16400]);
+
+        // Read the tail, which is shorter than the buffer.
+        let offset = expected.len() - 30;
+        assert_eq!(key_value.read_at(offset, &mut buffer).unwrap(), 30);
+        assert_eq!(buffer[..30], expected[offset..]);
+        assert_eq!(key_value.read_at(expected.len(), &mut buffer).unwrap(), 0);
+        assert_eq!(key_value.read_at(usize::MAX, &mut buffer).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_spare() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.spare(), 0);
+
+        let spare_offset = 4096
+            + key_value.cell_offset() as usize
+            + 4
+            + memoffset::offset_of!(super::KeyValueHeader, spare);
+        testhive[spare_offset..spare_offset + 2].copy_from_slice(&0x1234u16.to_le_bytes());
+
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.spare(), 0x1234);
+    }
+
+    #[test]
+    fn test_string_data_strip_bom() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "sz-with-bom",
+                KeyValueDataType::RegSZ,
+                &encode_sz("\u{feff}sz-test"),
+            )
+            .set_value(
+                "data-test",
+                "sz-with-reversed-bom",
+                KeyValueDataType::RegSZ,
+                &[0xfe, 0xff, b's', 0, b'z', 0],
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("sz-with-bom").unwrap().unwrap();
+        assert_eq!(key_value.string_data().unwrap(), "\u{feff}sz-test");
+        assert_eq!(key_value.string_data_strip_bom().unwrap(), "sz-test");
+
+        let key_value = key_node.value("sz-with-reversed-bom").unwrap().unwrap();
+        assert_eq!(key_value.string_data_strip_bom().unwrap(), "\u{fffe}sz");
+
+        let key_value = key_node.value("reg-sz").unwrap().unwrap();
+        assert_eq!(key_value.string_data_strip_bom().unwrap(), "sz-test");
+    }
+
+    #[test]
+    fn test_string_or_multi_string() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("reg-sz").unwrap().unwrap();
+        assert_eq!(key_value.string_or_multi_string().unwrap(), ["sz-test"]);
+
+        let key_value = key_node.value("reg-expand-sz").unwrap().unwrap();
+        assert_eq!(
+            key_value.string_or_multi_string().unwrap(),
+            [key_value.string_data().unwrap()]
+        );
+
+        let key_value = key_node.value("reg-multi-sz").unwrap().unwrap();
+        assert_eq!(
+            key_value.string_or_multi_string().unwrap(),
+            key_value
+                .multi_string_data()
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        );
+
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert!(matches!(
+            key_value.string_or_multi_string(),
+            Err(NtHiveError::InvalidKeyValueDataType {
+                actual: KeyValueDataType::RegDWord,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_string_data_utf8() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "utf8-sz",
+                KeyValueDataType::RegSZ,
+                "Grüße\0".as_bytes(),
+            )
+            .set_value(
+                "data-test",
+                "invalid-utf8-sz",
+                KeyValueDataType::RegExpandSZ,
+                b"a\xffb",
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value("utf8-sz").unwrap().unwrap();
+        assert_eq!(key_value.string_data_utf8().unwrap(), "Grüße");
+        assert_ne!(key_value.string_data().unwrap(), "Grüße");
+
+        let key_value = key_node.value("invalid-utf8-sz").unwrap().unwrap();
+        assert_eq!(key_value.string_data_utf8().unwrap(), "a\u{fffd}b");
+
+        // The data type is still checked.
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert!(matches!(
+            key_value.string_data_utf8(),
+            Err(NtHiveError::InvalidKeyValueDataType { .. })
+        ));
+    }
 }
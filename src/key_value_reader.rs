@@ -0,0 +1,135 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use zerocopy::SplitByteSlice;
+
+use crate::key_value::KeyValue;
+
+/// Adapter exposing the raw data of a [`KeyValue`] via [`Read`] and [`Seek`].
+///
+/// Reads are performed via [`KeyValue::read_at`], so only the Big Data segments covering the
+/// requested bytes are accessed, no matter where the reader has been seeked to.
+/// Seeking beyond the end of the data is allowed, subsequent reads then return 0 bytes.
+/// Hive errors are returned as [`io::Error`]s of kind [`io::ErrorKind::InvalidData`].
+pub struct KeyValueReader<'h, B: SplitByteSlice> {
+    key_value: KeyValue<'h, B>,
+    position: u64,
+}
+
+impl<'h, B> KeyValueReader<'h, B>
+where
+    B: SplitByteSlice,
+{
+    /// Creates a new `KeyValueReader` positioned at the beginning of the data of `key_value`.
+    pub fn new(key_value: KeyValue<'h, B>) -> Self {
+        Self {
+            key_value,
+            position: 0,
+        }
+    }
+
+    /// Returns the underlying [`KeyValue`].
+    pub fn into_inner(self) -> KeyValue<'h, B> {
+        self.key_value
+    }
+}
+
+impl<B> Read for KeyValueReader<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // The data size is a `u32`, so any position within the data fits into a `usize`.
+        if self.position >= u64::from(self.key_value.data_size()) {
+            return Ok(0);
+        }
+
+        let bytes_read = self.key_value.read_at(self.position as usize, buf)?;
+        self.position += bytes_read as u64;
+
+        Ok(bytes_read)
+    }
+}
+
+impl<B> Seek for KeyValueReader<'_, B>
+where
+    B: SplitByteSlice,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(offset) => (u64::from(self.key_value.data_size()), offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+
+        self.position = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use crate::*;
+
+    #[test]
+    fn test_key_value_reader() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+
+        for name in ["A", "C"] {
+            let key_value = key_node.value(name).unwrap().unwrap();
+            let expected_data = key_value.data().unwrap().into_vec().unwrap();
+            let mut reader = KeyValueReader::new(key_value);
+
+            // Read the entire data sequentially.
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).unwrap();
+            assert_eq!(data, expected_data);
+
+            // Seek to the end and read backwards in chunks, crossing Big Data segment boundaries.
+            let end = reader.seek(SeekFrom::End(0)).unwrap();
+            assert_eq!(end, expected_data.len() as u64);
+
+            let mut chunks = Vec::new();
+            let mut position = end;
+            while position > 0 {
+                let chunk_size = position.min(1000);
+                position = reader
+                    .seek(SeekFrom::Current(-(chunk_size as i64)))
+                    .unwrap();
+
+                let mut chunk = vec![0; chunk_size as usize];
+                reader.read_exact(&mut chunk).unwrap();
+                reader.seek(SeekFrom::Start(position)).unwrap();
+                chunks.push(chunk);
+            }
+
+            let data = chunks.into_iter().rev().flatten().collect::<Vec<_>>();
+            assert_eq!(data, expected_data);
+        }
+
+        // Reads beyond the end return nothing, seeks before the beginning fail.
+        let key_value = key_node.value("C").unwrap().unwrap();
+        let mut reader = KeyValueReader::new(key_value);
+        reader.seek(SeekFrom::End(10)).unwrap();
+        assert_eq!(reader.read(&mut [0; 10]).unwrap(), 0);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let error = reader.seek(SeekFrom::Current(-1)).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -16,16 +16,16 @@ use crate::key_value::KeyValue;
 /// On-Disk Structure of a Key Values List item.
 #[allow(dead_code)]
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct KeyValuesListItem {
     key_value_offset: U32<LittleEndian>,
 }
 
 /// Byte range of a single Key Values list item returned by [`KeyValuesListItemRanges`].
-struct KeyValuesListItemRange(Range<usize>);
+pub(crate) struct KeyValuesListItemRange(Range<usize>);
 
 impl KeyValuesListItemRange {
-    fn key_value_offset<B>(&self, hive: &Hive<B>) -> u32
+    pub(crate) fn key_value_offset<B>(&self, hive: &Hive<B>) -> u32
     where
         B: SplitByteSlice,
     {
@@ -48,7 +48,7 @@ impl Deref for KeyValuesListItemRange {
 ///
 /// On-Disk Signature: `vk`
 #[derive(Clone)]
-struct KeyValuesListItemRanges {
+pub(crate) struct KeyValuesListItemRanges {
     items_range: Range<usize>,
 }
 
@@ -62,6 +62,7 @@ impl KeyValuesListItemRanges {
 
         let items_range = byte_subrange(&cell_range, byte_count).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "key values list",
                 offset: count_field_offset,
                 expected: byte_count,
                 actual: cell_range.len(),
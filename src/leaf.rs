@@ -16,13 +16,15 @@ use crate::helpers::byte_subrange;
 use crate::hive::Hive;
 use crate::index_root::IndexRootItemRange;
 use crate::key_node::{KeyNode, KeyNodeMut};
+#[cfg(feature = "alloc")]
+use crate::string::utf16_code_unit_to_uppercase;
 use crate::subkeys_list::SubkeysList;
 
 /// On-Disk Structure of a Fast Leaf item (On-Disk Signature: `lf`).
 /// They are supported since Windows NT 4.
 #[allow(dead_code)]
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct FastLeafItem {
     key_node_offset: U32<LittleEndian>,
     name_hint: [u8; 4],
@@ -30,9 +32,8 @@ struct FastLeafItem {
 
 /// On-Disk Structure of a Hash Leaf item (On-Disk Signature: `lh`).
 /// They are supported since Windows XP.
-#[allow(dead_code)]
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct HashLeafItem {
     key_node_offset: U32<LittleEndian>,
     name_hash: [u8; 4],
@@ -41,7 +42,7 @@ struct HashLeafItem {
 /// On-Disk Structure of an Index Leaf item (On-Disk Signature: `li`).
 /// They are supported in all Windows versions.
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct IndexLeafItem {
     key_node_offset: U32<LittleEndian>,
 }
@@ -83,6 +84,17 @@ impl LeafType {
     }
 }
 
+/// Calculates the `name_hash` of a Hash Leaf item from the UTF-16 code units of a key name.
+///
+/// This is a simple case-insensitive hash over all code units.
+#[cfg(feature = "alloc")]
+pub(crate) fn name_hash(name: &[u16]) -> u32 {
+    name.iter().fold(0u32, |hash, code_unit| {
+        hash.wrapping_mul(37)
+            .wrapping_add(utf16_code_unit_to_uppercase(*code_unit) as u32)
+    })
+}
+
 /// Byte range of a single Leaf item returned by [`LeafItemRanges`].
 pub(crate) struct LeafItemRange(Range<usize>);
 
@@ -130,6 +142,7 @@ impl LeafItemRanges {
 
         let items_range = byte_subrange(&data_range, byte_count).ok_or_else(|| {
             NtHiveError::InvalidSizeField {
+                context: "leaf",
                 offset: count_field_offset,
                 expected: byte_count,
                 actual: data_range.len(),
@@ -162,6 +175,7 @@ impl LeafItemRanges {
         // Index Roots exist.
         if count == 0 {
             return Err(NtHiveError::InvalidSizeField {
+                context: "leaf",
                 offset: count_field_offset,
                 expected: 1,
                 actual: 0,
@@ -304,6 +318,61 @@ where
 impl<B> ExactSizeIterator for LeafKeyNodes<'_, B> where B: SplitByteSlice {}
 impl<B> FusedIterator for LeafKeyNodes<'_, B> where B: SplitByteSlice {}
 
+/// Iterator over
+///   a contiguous range of data bytes containing Hash Leaf items,
+///   returning the Key Node offset and the stored name hash of each item.
+///
+/// Returned by [`KeyNode::hash_leaf_entries`].
+/// The stored hashes can be compared to the hashes of the actual key names to detect
+/// inconsistencies or research hash collisions.
+///
+/// On-Disk Signature: `lh`
+///
+/// [`KeyNode::hash_leaf_entries`]: crate::key_node::KeyNode::hash_leaf_entries
+#[derive(Clone)]
+pub struct HashLeafEntries<'h, B: SplitByteSlice> {
+    hive: &'h Hive<B>,
+    leaf_item_ranges: LeafItemRanges,
+}
+
+impl<'h, B> HashLeafEntries<'h, B>
+where
+    B: SplitByteSlice,
+{
+    /// Returns `None` if `leaf_item_ranges` doesn't belong to a Hash Leaf.
+    pub(crate) fn new(hive: &'h Hive<B>, leaf_item_ranges: LeafItemRanges) -> Option<Self> {
+        matches!(leaf_item_ranges.leaf_type, LeafType::Hash).then_some(Self {
+            hive,
+            leaf_item_ranges,
+        })
+    }
+}
+
+impl<B> Iterator for HashLeafEntries<'_, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let leaf_item_range = self.leaf_item_ranges.next()?;
+        let hash_leaf_item =
+            Ref::<&[u8], HashLeafItem>::from_bytes(&self.hive.data[leaf_item_range.0]).unwrap();
+
+        Some((
+            hash_leaf_item.key_node_offset.get(),
+            u32::from_le_bytes(hash_leaf_item.name_hash),
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.leaf_item_ranges.size_hint()
+    }
+}
+
+impl<B> ExactSizeIterator for HashLeafEntries<'_, B> where B: SplitByteSlice {}
+impl<B> FusedIterator for HashLeafEntries<'_, B> where B: SplitByteSlice {}
+
 /// Iterator over
 ///   a contiguous range of data bytes containing Leaf items of any type (Fast/Hash/Index),
 ///   returning a mutable [`KeyNode`] for each Leaf item,
@@ -22,24 +22,49 @@ mod helpers;
 mod big_data;
 mod error;
 mod hive;
+mod hive_bin;
 mod index_root;
 mod key_node;
+mod key_security;
+#[cfg(feature = "alloc")]
+mod key_snapshot;
 mod key_value;
+#[cfg(feature = "std")]
+mod key_value_reader;
 mod key_values_list;
 mod leaf;
+#[cfg(feature = "alloc")]
+mod patch;
+#[cfg(feature = "std")]
+mod reader;
+mod resource_list;
 mod string;
 mod subkeys_list;
+#[cfg(feature = "alloc")]
+mod traversal;
 
 pub use crate::big_data::*;
 pub use crate::error::*;
 pub use crate::hive::*;
+pub use crate::hive_bin::*;
 pub use crate::index_root::*;
 pub use crate::key_node::*;
+#[cfg(feature = "alloc")]
+pub use crate::key_snapshot::*;
 pub use crate::key_value::*;
+#[cfg(feature = "std")]
+pub use crate::key_value_reader::*;
 pub use crate::key_values_list::*;
 pub use crate::leaf::*;
+#[cfg(feature = "alloc")]
+pub use crate::patch::*;
+#[cfg(feature = "std")]
+pub use crate::reader::*;
+pub use crate::resource_list::*;
 pub use crate::string::*;
 pub use crate::subkeys_list::*;
+#[cfg(feature = "alloc")]
+pub use crate::traversal::*;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -0,0 +1,1009 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+use core::ops::Range;
+
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout, Ref, SplitByteSlice, Unaligned, I32, U16, U32,
+    U64,
+};
+
+use crate::big_data::{BigDataHeader, BIG_DATA_SEGMENT_SIZE};
+use crate::error::{NtHiveError, Result};
+use crate::hive::{BaseBlock, CellHeader, Hive, HiveFeature};
+use crate::hive_bin::{HiveBinHeader, CELL_ALIGNMENT, HIVE_BIN_ALIGNMENT};
+use crate::key_node::{KeyNode, KeyNodeFlags, KeyNodeHeader};
+use crate::key_security::KeySecurityHeader;
+use crate::key_value::{
+    KeyValueDataType, KeyValueFlags, KeyValueHeader, DATA_STORED_IN_DATA_OFFSET,
+};
+use crate::leaf::name_hash;
+use crate::string::NtHiveNameString;
+use crate::subkeys_list::SubkeysListHeader;
+
+/// Maximum number of items we put into a single Leaf.
+/// Subkeys exceeding this number are spread over multiple Leafs referenced by an Index Root.
+const LEAF_ITEM_COUNT_MAX: usize = 511;
+
+/// A single edit recorded by [`HivePatch`].
+#[derive(Clone, Debug)]
+enum HivePatchOperation {
+    CreateKey {
+        path: String,
+    },
+    DeleteKey {
+        path: String,
+    },
+    SetValue {
+        path: String,
+        name: String,
+        data_type: KeyValueDataType,
+        data: Vec<u8>,
+    },
+    DeleteValue {
+        path: String,
+        name: String,
+    },
+}
+
+/// A list of edits that can be applied to a [`Hive`] to produce a modified hive.
+///
+/// Edits are recorded through the builder methods and later applied in the same order
+/// by [`HivePatch::apply`].
+/// Paths are interpreted like in [`KeyNode::subpath`], relative to the root key.
+///
+/// The source hive is never modified.
+/// Instead, [`HivePatch::apply`] copies it and appends new Hive Bins for all new cells.
+/// Cells that are no longer referenced are marked as unallocated, but their space is not reused.
+#[derive(Clone, Debug, Default)]
+pub struct HivePatch {
+    operations: Vec<HivePatchOperation>,
+    timestamp: Option<u64>,
+}
+
+impl HivePatch {
+    /// Creates an empty `HivePatch`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies all recorded edits to a copy of `hive` and returns the bytes of the new hive.
+    ///
+    /// The new hive gets an incremented sequence number and a recalculated checksum,
+    /// so it can be opened again via [`Hive::new`].
+    pub fn apply<B>(&self, hive: &Hive<B>) -> Result<Vec<u8>>
+    where
+        B: SplitByteSlice,
+    {
+        hive.validate_data_size()?;
+        let mut writer = HiveWriter::new(hive, self.timestamp);
+
+        for operation in &self.operations {
+            match operation {
+                HivePatchOperation::CreateKey { path } => {
+                    writer.create_key(path)?;
+                }
+                HivePatchOperation::DeleteKey { path } => writer.delete_key(path)?,
+                HivePatchOperation::SetValue {
+                    path,
+                    name,
+                    data_type,
+                    data,
+                } => writer.set_value(path, name, *data_type, data)?,
+                HivePatchOperation::DeleteValue { path, name } => {
+                    writer.delete_value(path, name)?
+                }
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Creates the key at `path`, including all missing parent keys.
+    ///
+    /// Nothing is done if the key already exists.
+    pub fn create_key(&mut self, path: &str) -> &mut Self {
+        self.operations.push(HivePatchOperation::CreateKey {
+            path: path.to_owned(),
+        });
+        self
+    }
+
+    /// Deletes the key at `path` along with all its subkeys and values.
+    pub fn delete_key(&mut self, path: &str) -> &mut Self {
+        self.operations.push(HivePatchOperation::DeleteKey {
+            path: path.to_owned(),
+        });
+        self
+    }
+
+    /// Deletes the value called `name` from the key at `path`.
+    pub fn delete_value(&mut self, path: &str, name: &str) -> &mut Self {
+        self.operations.push(HivePatchOperation::DeleteValue {
+            path: path.to_owned(),
+            name: name.to_owned(),
+        });
+        self
+    }
+
+    /// Sets the value called `name` of the key at `path` to the given raw data.
+    ///
+    /// An existing value of the same name is replaced.
+    /// The key must already exist, see [`HivePatch::create_key`].
+    pub fn set_value(
+        &mut self,
+        path: &str,
+        name: &str,
+        data_type: KeyValueDataType,
+        data: &[u8],
+    ) -> &mut Self {
+        self.operations.push(HivePatchOperation::SetValue {
+            path: path.to_owned(),
+            name: name.to_owned(),
+            data_type,
+            data: data.to_vec(),
+        });
+        self
+    }
+
+    /// Sets the timestamp (in FILETIME format) written to the base block and to every
+    /// created or modified key.
+    ///
+    /// If no timestamp is set, created keys get a zero timestamp and all other timestamps
+    /// are left unchanged.
+    pub fn timestamp(&mut self, timestamp: u64) -> &mut Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+/// A subkey or value referenced by a Key Node, along with its name in UTF-16 code units.
+struct NamedCell {
+    offset: u32,
+    name: Vec<u16>,
+}
+
+impl NamedCell {
+    fn cmp_name(&self, name: &[u16]) -> Ordering {
+        NtHiveNameString::cmp_iter(self.name.iter().copied(), name.iter().copied())
+    }
+}
+
+/// Append-only writer performing the edits of a [`HivePatch`] on a copy of the hive bytes.
+struct HiveWriter {
+    bytes: Vec<u8>,
+    free_cell_range: Option<Range<usize>>,
+    minor_version: u32,
+    timestamp: Option<u64>,
+}
+
+impl HiveWriter {
+    fn new<B>(hive: &Hive<B>, timestamp: Option<u64>) -> Self
+    where
+        B: SplitByteSlice,
+    {
+        let base_block_bytes = Ref::bytes(&hive.base_block);
+        let data_size = hive.base_block.data_size.get() as usize;
+
+        let mut bytes = Vec::with_capacity(base_block_bytes.len() + data_size);
+        bytes.extend_from_slice(base_block_bytes);
+        bytes.extend_from_slice(&hive.data[..data_size]);
+
+        Self {
+            bytes,
+            free_cell_range: None,
+            minor_version: hive.minor_version(),
+            timestamp,
+        }
+    }
+
+    fn hive(&self) -> Result<Hive<&[u8]>> {
+        Hive::without_validation(self.bytes.as_slice())
+    }
+
+    /// Like [`Hive::supports`], but for the hive being written.
+    fn supports(&self, feature: HiveFeature) -> bool {
+        self.minor_version >= feature.minimum_minor_version() as u32
+    }
+
+    fn base_block_mut(&mut self) -> Ref<&mut [u8], BaseBlock> {
+        let (base_block, _) = Ref::from_prefix(self.bytes.as_mut_slice()).unwrap();
+        base_block
+    }
+
+    /// Returns the range of the cell data (without the cell header) inside `self.bytes`.
+    fn cell_range(&self, offset: u32) -> Result<Range<usize>> {
+        let hive = self.hive()?;
+        let cell_range = hive.cell_range_from_data_offset(offset)?;
+        let data_start = mem::size_of::<BaseBlock>();
+
+        Ok(data_start + cell_range.start..data_start + cell_range.end)
+    }
+
+    fn cell<T>(&self, offset: u32) -> Result<Ref<&[u8], T>>
+    where
+        T: FromBytes + Immutable + KnownLayout + Unaligned,
+    {
+        let cell_range = self.cell_range(offset)?;
+        let start = cell_range.start;
+        let length = cell_range.len();
+
+        let (header, _) = Ref::from_prefix(&self.bytes[cell_range]).map_err(|_| {
+            NtHiveError::InvalidHeaderSize {
+                context: "cell",
+                offset: start,
+                expected: mem::size_of::<T>(),
+                actual: length,
+            }
+        })?;
+
+        Ok(header)
+    }
+
+    fn cell_mut<T>(&mut self, offset: u32) -> Result<Ref<&mut [u8], T>>
+    where
+        T: FromBytes + Immutable + IntoBytes + KnownLayout + Unaligned,
+    {
+        let cell_range = self.cell_range(offset)?;
+        let start = cell_range.start;
+        let length = cell_range.len();
+
+        let (header, _) = Ref::from_prefix(&mut self.bytes[cell_range]).map_err(|_| {
+            NtHiveError::InvalidHeaderSize {
+                context: "cell",
+                offset: start,
+                expected: mem::size_of::<T>(),
+                actual: length,
+            }
+        })?;
+
+        Ok(header)
+    }
+
+    /// Reads `count` cell offsets starting at `start` bytes into the cell at `offset`.
+    fn cell_offsets(&self, offset: u32, start: usize, count: usize) -> Result<Vec<u32>> {
+        let cell_range = self.cell_range(offset)?;
+        let byte_count = start + count * mem::size_of::<u32>();
+
+        if cell_range.len() < byte_count {
+            return Err(NtHiveError::InvalidDataSize {
+                context: "cell",
+                offset: cell_range.start,
+                expected: byte_count,
+                actual: cell_range.len(),
+            });
+        }
+
+        let items_start = cell_range.start + start;
+        let items_end = cell_range.start + byte_count;
+        let offsets = self.bytes[items_start..items_end]
+            .chunks_exact(mem::size_of::<u32>())
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+
+        Ok(offsets)
+    }
+
+    fn find_key_node(&self, path: &str) -> Result<Option<u32>> {
+        let hive = self.hive()?;
+        let root_key_node = hive.root_key_node()?;
+        let key_node = root_key_node.subpath(path).transpose()?;
+
+        Ok(key_node.map(|key_node| key_node.offset()))
+    }
+
+    fn subkeys(&self, key_node_offset: u32) -> Result<Vec<NamedCell>> {
+        let hive = self.hive()?;
+        let cell_range = hive.cell_range_from_data_offset(key_node_offset)?;
+        let key_node = KeyNode::from_cell_range(&hive, cell_range)?;
+        let mut subkeys = Vec::new();
+
+        if let Some(iter) = key_node.subkeys() {
+            for subkey in iter? {
+                let subkey = subkey?;
+                subkeys.push(NamedCell {
+                    offset: subkey.offset(),
+                    name: code_units(&subkey.name()?),
+                });
+            }
+        }
+
+        Ok(subkeys)
+    }
+
+    fn values(&self, key_node_offset: u32) -> Result<Vec<NamedCell>> {
+        let hive = self.hive()?;
+        let cell_range = hive.cell_range_from_data_offset(key_node_offset)?;
+        let key_node = KeyNode::from_cell_range(&hive, cell_range)?;
+        let mut values = Vec::new();
+
+        if let Some(iter) = key_node.values() {
+            for value in iter? {
+                let value = value?;
+                values.push(NamedCell {
+                    offset: value.cell_offset(),
+                    name: code_units(&value.name()?),
+                });
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn allocate_cell(&mut self, data_size: usize) -> u32 {
+        let cell_size = (mem::size_of::<CellHeader>() + data_size).next_multiple_of(CELL_ALIGNMENT);
+
+        let free_cell_range = match self.free_cell_range.take() {
+            Some(free_cell_range) if free_cell_range.len() >= cell_size => free_cell_range,
+            _ => self.append_hive_bin(cell_size),
+        };
+
+        let cell_range = free_cell_range.start..free_cell_range.start + cell_size;
+        let remaining_range = cell_range.end..free_cell_range.end;
+
+        if !remaining_range.is_empty() {
+            // Keep the rest as an unallocated cell for the next allocation.
+            self.write_cell_header(remaining_range.start, remaining_range.len() as i32);
+            self.free_cell_range = Some(remaining_range);
+        }
+
+        self.bytes[cell_range.clone()].fill(0);
+        self.write_cell_header(cell_range.start, -(cell_size as i32));
+
+        (cell_range.start - mem::size_of::<BaseBlock>()) as u32
+    }
+
+    /// Appends a new Hive Bin large enough for a cell of `cell_size` bytes.
+    /// Returns the range of the free space inside the new Hive Bin.
+    fn append_hive_bin(&mut self, cell_size: usize) -> Range<usize> {
+        let bin_start = self.bytes.len();
+        let bin_size =
+            (mem::size_of::<HiveBinHeader>() + cell_size).next_multiple_of(HIVE_BIN_ALIGNMENT);
+
+        let header = HiveBinHeader {
+            signature: *b"hbin",
+            offset: U32::new((bin_start - mem::size_of::<BaseBlock>()) as u32),
+            size: U32::new(bin_size as u32),
+            reserved: [U32::ZERO; 2],
+            timestamp: U64::new(self.timestamp.unwrap_or(0)),
+            spare: U32::ZERO,
+        };
+
+        self.bytes.resize(bin_start + bin_size, 0);
+        self.bytes[bin_start..bin_start + mem::size_of::<HiveBinHeader>()]
+            .copy_from_slice(header.as_bytes());
+
+        bin_start + mem::size_of::<HiveBinHeader>()..bin_start + bin_size
+    }
+
+    fn write_cell_header(&mut self, position: usize, size: i32) {
+        let header = CellHeader {
+            size: I32::new(size),
+        };
+        self.bytes[position..position + mem::size_of::<CellHeader>()]
+            .copy_from_slice(header.as_bytes());
+    }
+
+    fn write_cell(&mut self, data: &[u8]) -> u32 {
+        let offset = self.allocate_cell(data.len());
+        let data_start = mem::size_of::<BaseBlock>() + offset as usize;
+        let data_start = data_start + mem::size_of::<CellHeader>();
+
+        self.bytes[data_start..data_start + data.len()].copy_from_slice(data);
+        offset
+    }
+
+    fn free_cell(&mut self, offset: u32) -> Result<()> {
+        let cell_range = self.cell_range(offset)?;
+        let header_start = cell_range.start - mem::size_of::<CellHeader>();
+
+        // A positive size marks the cell as unallocated.
+        let cell_size = cell_range.len() + mem::size_of::<CellHeader>();
+        self.write_cell_header(header_start, cell_size as i32);
+
+        Ok(())
+    }
+
+    fn create_key(&mut self, path: &str) -> Result<u32> {
+        if let Some(key_node_offset) = self.find_key_node(path)? {
+            return Ok(key_node_offset);
+        }
+
+        // `find_key_node` always finds the root key, so we must have a parent here.
+        let (parent_path, name) = split_parent_path(path).unwrap();
+        let parent_offset = self.create_key(parent_path)?;
+        self.add_key_node(parent_offset, name)
+    }
+
+    fn add_key_node(&mut self, parent_offset: u32, name: &str) -> Result<u32> {
+        // New keys share the security settings of their parent.
+        let key_security_offset = self
+            .cell::<KeyNodeHeader>(parent_offset)?
+            .key_security_offset
+            .get();
+        if key_security_offset != u32::MAX {
+            let mut key_security = self.cell_mut::<KeySecurityHeader>(key_security_offset)?;
+            let reference_count = key_security.reference_count.get();
+            key_security.reference_count.set(reference_count + 1);
+        }
+
+        let (name_bytes, is_latin1) = encode_name(name);
+        let flags = if is_latin1 {
+            KeyNodeFlags::KEY_COMP_NAME
+        } else {
+            KeyNodeFlags::empty()
+        };
+
+        let header = KeyNodeHeader {
+            signature: *b"nk",
+            flags: U16::new(flags.bits()),
+            timestamp: U64::new(self.timestamp.unwrap_or(0)),
+            spare: U32::ZERO,
+            parent: U32::new(parent_offset),
+            subkey_count: U32::ZERO,
+            volatile_subkey_count: U32::ZERO,
+            subkeys_list_offset: U32::new(u32::MAX),
+            volatile_subkeys_list_offset: U32::new(u32::MAX),
+            key_values_count: U32::ZERO,
+            key_values_list_offset: U32::new(u32::MAX),
+            key_security_offset: U32::new(key_security_offset),
+            class_name_offset: U32::new(u32::MAX),
+            max_subkey_name: U32::ZERO,
+            max_subkey_class_name: U32::ZERO,
+            max_value_name: U32::ZERO,
+            max_value_data: U32::ZERO,
+            work_var: U32::ZERO,
+            key_name_length: U16::new(name_bytes.len() as u16),
+            class_name_length: U16::ZERO,
+        };
+
+        let mut cell_data = header.as_bytes().to_vec();
+        cell_data.extend_from_slice(&name_bytes);
+        let key_node_offset = self.write_cell(&cell_data);
+
+        // Insert the new Key Node into the sorted subkeys of its parent.
+        let name: Vec<u16> = name.encode_utf16().collect();
+        let name_size = (name.len() * mem::size_of::<u16>()) as u32;
+
+        let mut subkeys = self.subkeys(parent_offset)?;
+        let index = subkeys
+            .iter()
+            .position(|subkey| subkey.cmp_name(&name) == Ordering::Greater)
+            .unwrap_or(subkeys.len());
+        subkeys.insert(
+            index,
+            NamedCell {
+                offset: key_node_offset,
+                name,
+            },
+        );
+        self.write_subkeys_list(parent_offset, &subkeys)?;
+
+        let mut parent = self.cell_mut::<KeyNodeHeader>(parent_offset)?;
+        // Only the lower 16 bits hold the length, the upper ones are used for flags.
+        let max_subkey_name = parent.max_subkey_name.get();
+        if max_subkey_name & 0xffff < name_size {
+            parent
+                .max_subkey_name
+                .set(max_subkey_name & !0xffff | name_size);
+        }
+        self.touch_key_node(parent_offset)?;
+
+        Ok(key_node_offset)
+    }
+
+    fn delete_key(&mut self, path: &str) -> Result<()> {
+        if split_parent_path(path).is_none() {
+            return Err(NtHiveError::CannotDeleteRootKey);
+        }
+
+        let key_node_offset =
+            self.find_key_node(path)?
+                .ok_or_else(|| NtHiveError::KeyNotFound {
+                    path: path.to_owned(),
+                })?;
+        let parent_offset = self.cell::<KeyNodeHeader>(key_node_offset)?.parent.get();
+
+        let mut subkeys = self.subkeys(parent_offset)?;
+        subkeys.retain(|subkey| subkey.offset != key_node_offset);
+        self.write_subkeys_list(parent_offset, &subkeys)?;
+        self.touch_key_node(parent_offset)?;
+
+        self.free_key_node(key_node_offset)
+    }
+
+    fn free_key_node(&mut self, key_node_offset: u32) -> Result<()> {
+        for subkey in self.subkeys(key_node_offset)? {
+            self.free_key_node(subkey.offset)?;
+        }
+
+        for value in self.values(key_node_offset)? {
+            self.free_key_value(value.offset)?;
+        }
+
+        let header = self.cell::<KeyNodeHeader>(key_node_offset)?;
+        let subkeys_list_offset = header.subkeys_list_offset.get();
+        let key_values_list_offset = header.key_values_list_offset.get();
+        let key_security_offset = header.key_security_offset.get();
+        let class_name_offset = header.class_name_offset.get();
+
+        self.free_subkeys_list(subkeys_list_offset)?;
+        if key_values_list_offset != u32::MAX {
+            self.free_cell(key_values_list_offset)?;
+        }
+        if class_name_offset != u32::MAX {
+            self.free_cell(class_name_offset)?;
+        }
+        self.release_key_security(key_security_offset)?;
+
+        self.free_cell(key_node_offset)
+    }
+
+    fn release_key_security(&mut self, key_security_offset: u32) -> Result<()> {
+        if key_security_offset == u32::MAX {
+            return Ok(());
+        }
+
+        let mut key_security = self.cell_mut::<KeySecurityHeader>(key_security_offset)?;
+        let reference_count = key_security.reference_count.get();
+        if reference_count > 1 {
+            key_security.reference_count.set(reference_count - 1);
+            return Ok(());
+        }
+
+        // This was the last reference, so unlink the Key Security cell from the
+        // doubly linked list of all Key Security cells and free it.
+        let flink = key_security.flink.get();
+        let blink = key_security.blink.get();
+
+        self.cell_mut::<KeySecurityHeader>(blink)?.flink.set(flink);
+        self.cell_mut::<KeySecurityHeader>(flink)?.blink.set(blink);
+        self.free_cell(key_security_offset)
+    }
+
+    fn free_subkeys_list(&mut self, subkeys_list_offset: u32) -> Result<()> {
+        if subkeys_list_offset == u32::MAX {
+            return Ok(());
+        }
+
+        let header = self.cell::<SubkeysListHeader>(subkeys_list_offset)?;
+        let signature = header.signature;
+        let count = header.count.get() as usize;
+
+        if &signature == b"ri" {
+            let leaf_offsets = self.cell_offsets(
+                subkeys_list_offset,
+                mem::size_of::<SubkeysListHeader>(),
+                count,
+            )?;
+
+            for leaf_offset in leaf_offsets {
+                self.free_cell(leaf_offset)?;
+            }
+        }
+
+        self.free_cell(subkeys_list_offset)
+    }
+
+    fn write_subkeys_list(&mut self, key_node_offset: u32, subkeys: &[NamedCell]) -> Result<()> {
+        let old_subkeys_list_offset = self
+            .cell::<KeyNodeHeader>(key_node_offset)?
+            .subkeys_list_offset
+            .get();
+        self.free_subkeys_list(old_subkeys_list_offset)?;
+
+        let subkeys_list_offset = if subkeys.is_empty() {
+            u32::MAX
+        } else if subkeys.len() <= LEAF_ITEM_COUNT_MAX {
+            self.write_leaf(subkeys)
+        } else {
+            let leaf_offsets: Vec<u32> = subkeys
+                .chunks(LEAF_ITEM_COUNT_MAX)
+                .map(|chunk| self.write_leaf(chunk))
+                .collect();
+            self.write_subkeys_list_cell(b"ri", leaf_offsets.iter().map(|offset| (*offset, None)))
+        };
+
+        let mut header = self.cell_mut::<KeyNodeHeader>(key_node_offset)?;
+        header.subkey_count.set(subkeys.len() as u32);
+        header.subkeys_list_offset.set(subkeys_list_offset);
+
+        Ok(())
+    }
+
+    fn write_leaf(&mut self, subkeys: &[NamedCell]) -> u32 {
+        // Hash Leafs are supported since Windows XP, older versions get Fast Leafs.
+        if self.supports(HiveFeature::HashLeafs) {
+            let items = subkeys
+                .iter()
+                .map(|subkey| (subkey.offset, Some(name_hash(&subkey.name).to_le_bytes())));
+            self.write_subkeys_list_cell(b"lh", items)
+        } else {
+            let items = subkeys
+                .iter()
+                .map(|subkey| (subkey.offset, Some(name_hint(&subkey.name))));
+            self.write_subkeys_list_cell(b"lf", items)
+        }
+    }
+
+    fn write_subkeys_list_cell<I>(&mut self, signature: &[u8; 2], items: I) -> u32
+    where
+        I: ExactSizeIterator<Item = (u32, Option<[u8; 4]>)>,
+    {
+        let header = SubkeysListHeader {
+            signature: *signature,
+            count: U16::new(items.len() as u16),
+        };
+        let mut cell_data = header.as_bytes().to_vec();
+
+        for (offset, extra) in items {
+            cell_data.extend_from_slice(&offset.to_le_bytes());
+            if let Some(extra) = extra {
+                cell_data.extend_from_slice(&extra);
+            }
+        }
+
+        self.write_cell(&cell_data)
+    }
+
+    fn set_value(
+        &mut self,
+        path: &str,
+        name: &str,
+        data_type: KeyValueDataType,
+        data: &[u8],
+    ) -> Result<()> {
+        let key_node_offset =
+            self.find_key_node(path)?
+                .ok_or_else(|| NtHiveError::KeyNotFound {
+                    path: path.to_owned(),
+                })?;
+
+        let key_value_offset = self.write_key_value(name, data_type, data);
+        let name: Vec<u16> = name.encode_utf16().collect();
+        let name_size = (name.len() * mem::size_of::<u16>()) as u32;
+
+        let mut values = self.values(key_node_offset)?;
+        match values
+            .iter()
+            .position(|value| value.cmp_name(&name) == Ordering::Equal)
+        {
+            Some(index) => {
+                self.free_key_value(values[index].offset)?;
+                values[index].offset = key_value_offset;
+            }
+            None => values.push(NamedCell {
+                offset: key_value_offset,
+                name,
+            }),
+        }
+        self.write_values_list(key_node_offset, &values)?;
+
+        let mut header = self.cell_mut::<KeyNodeHeader>(key_node_offset)?;
+        if header.max_value_name.get() < name_size {
+            header.max_value_name.set(name_size);
+        }
+        if header.max_value_data.get() < data.len() as u32 {
+            header.max_value_data.set(data.len() as u32);
+        }
+
+        self.touch_key_node(key_node_offset)
+    }
+
+    fn write_key_value(&mut self, name: &str, data_type: KeyValueDataType, data: &[u8]) -> u32 {
+        let (data_size, data_offset) = if data.len() <= mem::size_of::<u32>() {
+            // Small data is stored directly in the `data_offset` field.
+            let mut data_bytes = [0u8; mem::size_of::<u32>()];
+            data_bytes[..data.len()].copy_from_slice(data);
+
+            (
+                data.len() as u32 | DATA_STORED_IN_DATA_OFFSET,
+                u32::from_le_bytes(data_bytes),
+            )
+        } else if data.len() <= BIG_DATA_SEGMENT_SIZE || !self.supports(HiveFeature::BigData) {
+            (data.len() as u32, self.write_cell(data))
+        } else {
+            (data.len() as u32, self.write_big_data(data))
+        };
+
+        let (name_bytes, is_latin1) = encode_name(name);
+        let flags = if is_latin1 {
+            KeyValueFlags::VALUE_COMP_NAME
+        } else {
+            KeyValueFlags::empty()
+        };
+
+        let header = KeyValueHeader {
+            signature: *b"vk",
+            name_length: U16::new(name_bytes.len() as u16),
+            data_size: U32::new(data_size),
+            data_offset: U32::new(data_offset),
+            data_type: U32::new(data_type as u32),
+            flags: U16::new(flags.bits()),
+            spare: U16::ZERO,
+        };
+
+        let mut cell_data = header.as_bytes().to_vec();
+        cell_data.extend_from_slice(&name_bytes);
+        self.write_cell(&cell_data)
+    }
+
+    fn write_big_data(&mut self, data: &[u8]) -> u32 {
+        let segment_list: Vec<u8> = data
+            .chunks(BIG_DATA_SEGMENT_SIZE)
+            .flat_map(|segment| self.write_cell(segment).to_le_bytes())
+            .collect();
+        let segment_count = segment_list.len() / mem::size_of::<u32>();
+        let segment_list_offset = self.write_cell(&segment_list);
+
+        let header = BigDataHeader {
+            signature: *b"db",
+            segment_count: U16::new(segment_count as u16),
+            segment_list_offset: U32::new(segment_list_offset),
+        };
+        self.write_cell(header.as_bytes())
+    }
+
+    fn free_key_value(&mut self, key_value_offset: u32) -> Result<()> {
+        let header = self.cell::<KeyValueHeader>(key_value_offset)?;
+        let data_size = header.data_size.get();
+        let data_offset = header.data_offset.get();
+
+        if data_size & DATA_STORED_IN_DATA_OFFSET == 0 && data_offset != u32::MAX {
+            if data_size as usize > BIG_DATA_SEGMENT_SIZE && self.supports(HiveFeature::BigData) {
+                let header = self.cell::<BigDataHeader>(data_offset)?;
+                let segment_count = header.segment_count.get() as usize;
+                let segment_list_offset = header.segment_list_offset.get();
+
+                for segment_offset in self.cell_offsets(segment_list_offset, 0, segment_count)? {
+                    self.free_cell(segment_offset)?;
+                }
+                self.free_cell(segment_list_offset)?;
+            }
+
+            self.free_cell(data_offset)?;
+        }
+
+        self.free_cell(key_value_offset)
+    }
+
+    fn delete_value(&mut self, path: &str, name: &str) -> Result<()> {
+        let key_node_offset =
+            self.find_key_node(path)?
+                .ok_or_else(|| NtHiveError::KeyNotFound {
+                    path: path.to_owned(),
+                })?;
+
+        let name_units: Vec<u16> = name.encode_utf16().collect();
+        let mut values = self.values(key_node_offset)?;
+        let index = values
+            .iter()
+            .position(|value| value.cmp_name(&name_units) == Ordering::Equal)
+            .ok_or_else(|| NtHiveError::ValueNotFound {
+                path: path.to_owned(),
+                name: name.to_owned(),
+            })?;
+
+        let value = values.remove(index);
+        self.free_key_value(value.offset)?;
+        self.write_values_list(key_node_offset, &values)?;
+
+        self.touch_key_node(key_node_offset)
+    }
+
+    fn write_values_list(&mut self, key_node_offset: u32, values: &[NamedCell]) -> Result<()> {
+        let old_key_values_list_offset = self
+            .cell::<KeyNodeHeader>(key_node_offset)?
+            .key_values_list_offset
+            .get();
+        if old_key_values_list_offset != u32::MAX {
+            self.free_cell(old_key_values_list_offset)?;
+        }
+
+        let key_values_list_offset = if values.is_empty() {
+            u32::MAX
+        } else {
+            let cell_data: Vec<u8> = values
+                .iter()
+                .flat_map(|value| value.offset.to_le_bytes())
+                .collect();
+            self.write_cell(&cell_data)
+        };
+
+        let mut header = self.cell_mut::<KeyNodeHeader>(key_node_offset)?;
+        header.key_values_count.set(values.len() as u32);
+        header.key_values_list_offset.set(key_values_list_offset);
+
+        Ok(())
+    }
+
+    fn touch_key_node(&mut self, key_node_offset: u32) -> Result<()> {
+        if let Some(timestamp) = self.timestamp {
+            let mut header = self.cell_mut::<KeyNodeHeader>(key_node_offset)?;
+            header.timestamp.set(timestamp);
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>> {
+        let data_size = self.bytes.len() - mem::size_of::<BaseBlock>();
+        let timestamp = self.timestamp;
+
+        let mut base_block = self.base_block_mut();
+        let sequence_number = base_block.primary_sequence_number.get().wrapping_add(1);
+        base_block.primary_sequence_number.set(sequence_number);
+        base_block.secondary_sequence_number.set(sequence_number);
+        base_block.data_size.set(data_size as u32);
+        if let Some(timestamp) = timestamp {
+            base_block.timestamp.set(timestamp);
+        }
+
+        let checksum = self.hive()?.calculate_checksum();
+        self.base_block_mut().checksum.set(checksum);
+
+        Ok(self.bytes)
+    }
+}
+
+/// Returns the UTF-16 code units of a name, no matter how it is stored.
+fn code_units(name: &NtHiveNameString) -> Vec<u16> {
+    match name {
+        NtHiveNameString::Latin1(bytes) => bytes.iter().map(|byte| *byte as u16).collect(),
+        NtHiveNameString::Utf16LE(bytes) => bytes
+            .chunks_exact(2)
+            .map(|two_bytes| u16::from_le_bytes(two_bytes.try_into().unwrap()))
+            .collect(),
+    }
+}
+
+/// Encodes a name for storing it in a Key Node or Key Value.
+/// Like Windows, we store the name in Latin1 if possible and in UTF-16LE otherwise.
+/// Returns the encoded bytes and whether they are Latin1.
+fn encode_name(name: &str) -> (Vec<u8>, bool) {
+    if name.chars().all(|c| (c as u32) <= 0xff) {
+        (name.chars().map(|c| c as u8).collect(), true)
+    } else {
+        let bytes = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        (bytes, false)
+    }
+}
+
+/// Returns the first 4 characters of a name as stored in a Fast Leaf item.
+fn name_hint(name: &[u16]) -> [u8; 4] {
+    let mut hint = [0u8; 4];
+
+    for (byte, code_unit) in hint.iter_mut().zip(name) {
+        *byte = *code_unit as u8;
+    }
+
+    hint
+}
+
+/// Splits a path into the path of the parent key and the name of the last key.
+/// Returns `None` if the path refers to the root key.
+fn split_parent_path(path: &str) -> Option<(&str, &str)> {
+    let path = path.trim_matches('\\');
+    if path.is_empty() {
+        return None;
+    }
+
+    Some(path.rsplit_once('\\').unwrap_or(("", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_keys() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        let patched = HivePatch::new()
+            .create_key("subkey-test\\key512")
+            .create_key("new-key\\äöü\\𐐐")
+            .delete_key("subpath-test")
+            .apply(&hive)
+            .unwrap();
+        let patched_hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = patched_hive.root_key_node().unwrap();
+
+        // Adding a 513th key must keep all subkeys accessible.
+        let key_node = root_key_node.subkey("subkey-test").unwrap().unwrap();
+        assert_eq!(key_node.subkeys().unwrap().unwrap().count(), 513);
+        for i in 0..=512 {
+            let name = format!("key{i}");
+            let subkey = key_node.subkey(&name).unwrap().unwrap();
+            assert_eq!(subkey.name().unwrap(), name.as_str());
+        }
+
+        let key_node = root_key_node.subpath("new-key\\ÄÖÜ\\𐐐").unwrap().unwrap();
+        assert_eq!(key_node.name().unwrap(), "𐐐");
+        assert!(root_key_node.subkey("subpath-test").is_none());
+
+        // Check the error cases.
+        assert!(matches!(
+            HivePatch::new().delete_key("\\").apply(&hive),
+            Err(NtHiveError::CannotDeleteRootKey)
+        ));
+        assert!(matches!(
+            HivePatch::new().delete_key("no-such-key").apply(&hive),
+            Err(NtHiveError::KeyNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_value() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "new-dword",
+                KeyValueDataType::RegDWord,
+                &1234u32.to_le_bytes(),
+            )
+            .apply(&hive)
+            .unwrap();
+        let patched_hive = Hive::new(patched.as_ref()).unwrap();
+        let key_node = patched_hive
+            .root_key_node()
+            .unwrap()
+            .subkey("data-test")
+            .unwrap()
+            .unwrap();
+
+        let key_value = key_node.value("new-dword").unwrap().unwrap();
+        assert_eq!(key_value.dword_data().unwrap(), 1234);
+
+        // Existing values must be unaffected.
+        let key_value = key_node.value("dword").unwrap().unwrap();
+        assert_eq!(key_value.dword_data().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_values() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let big_data = vec![b'D'; 40000];
+
+        let patched = HivePatch::new()
+            .set_value("data-test", "BINARY", KeyValueDataType::RegBinary, &[6, 7])
+            .set_value("data-test", "big", KeyValueDataType::RegBinary, &big_data)
+            .set_value("data-test", "empty", KeyValueDataType::RegNone, &[])
+            .delete_value("big-data-test", "C")
+            .apply(&hive)
+            .unwrap();
+        let patched_hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = patched_hive.root_key_node().unwrap();
+
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert_eq!(key_node.values().unwrap().unwrap().count(), 11);
+
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert_eq!(key_value.data().unwrap().into_vec().unwrap(), vec![6, 7]);
+
+        let key_value = key_node.value("big").unwrap().unwrap();
+        assert!(matches!(key_value.data().unwrap(), KeyValueData::Big(_)));
+        assert_eq!(key_value.data().unwrap().into_vec().unwrap(), big_data);
+
+        let key_value = key_node.value("empty").unwrap().unwrap();
+        assert_eq!(key_value.data().unwrap().into_vec().unwrap(), vec![]);
+
+        let key_node = root_key_node.subkey("big-data-test").unwrap().unwrap();
+        assert!(key_node.value("C").is_none());
+        assert!(matches!(
+            HivePatch::new()
+                .delete_value("big-data-test", "C")
+                .apply(&patched_hive),
+            Err(NtHiveError::ValueNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,455 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use std::collections::BTreeSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::mem;
+use std::ops::Range;
+use std::vec;
+
+use crate::error::{NtHiveError, Result};
+use crate::hive::{BaseBlock, CellHeader, Hive};
+use crate::index_root::IndexRootItemRanges;
+use crate::key_node::KeyNode;
+use crate::key_value::{KeyValue, KeyValueDataType};
+use crate::key_values_list::KeyValuesListItemRanges;
+use crate::leaf::{LeafItemRanges, LeafType};
+use crate::subkeys_list::SubkeysList;
+
+/// A structural event returned by [`HiveReader`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// A key has been entered.
+    /// All events up to the matching [`Event::LeaveKey`] belong to this key.
+    EnterKey(String),
+    /// The key entered last has been left.
+    LeaveKey,
+    /// A value of the key entered last.
+    Value {
+        name: String,
+        data_type: KeyValueDataType,
+        data_len: u32,
+    },
+}
+
+/// Pending values and subkeys of a key entered by [`HiveReader`].
+struct KeyFrame {
+    key_value_offsets: vec::IntoIter<u32>,
+    subkey_offsets: vec::IntoIter<u32>,
+}
+
+/// Pull-based reader returning structural [`Event`]s for all keys and values of a hive,
+/// depth-first and in the same order as [`KeyNode::subkeys`] and [`KeyNode::values`].
+///
+/// Unlike [`Hive`], this doesn't need the entire hive in memory.
+/// It seeks to every cell it needs and only reads that cell.
+/// Value data is never read.
+/// A Key Node reached a second time is reported as [`NtHiveError::KeyNodeCycle`].
+pub struct HiveReader<R: Read + Seek> {
+    reader: R,
+    /// Base block of the hive followed by the cell read last, see [`Hive::from_single_cell`].
+    cell_buffer: Vec<u8>,
+    /// Length of the hive data that is actually available in the stream.
+    data_len: usize,
+    root_cell_offset: u32,
+    stack: Vec<KeyFrame>,
+    visited: BTreeSet<u32>,
+    started: bool,
+    finished: bool,
+}
+
+impl<R> HiveReader<R>
+where
+    R: Read + Seek,
+{
+    /// Creates a new `HiveReader` after reading and validating the base block of the hive.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut base_block_bytes = [0u8; mem::size_of::<BaseBlock>()];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut base_block_bytes)?;
+
+        let hive = Hive::without_validation(&base_block_bytes[..])?;
+        hive.validate_base_block()?;
+
+        let data_size = hive.base_block().data_size() as u64;
+        let root_cell_offset = hive.base_block().root_cell_offset();
+
+        // Never trust the declared data size beyond the end of the stream.
+        let stream_len = reader.seek(SeekFrom::End(0))?;
+        let data_len = data_size.min(stream_len.saturating_sub(mem::size_of::<BaseBlock>() as u64));
+
+        Ok(Self {
+            reader,
+            cell_buffer: base_block_bytes.to_vec(),
+            data_len: data_len as usize,
+            root_cell_offset,
+            stack: Vec::new(),
+            visited: BTreeSet::new(),
+            started: false,
+            finished: false,
+        })
+    }
+
+    /// Reads the cell at `data_offset` and returns a [`Hive`] containing only that cell
+    /// along with the range of its data, performing the same checks as [`Hive`] does.
+    fn read_cell(&mut self, data_offset: u32) -> Result<(Hive<&[u8]>, Range<usize>)> {
+        let file_offset = mem::size_of::<BaseBlock>() + data_offset as usize;
+        let remaining = self.data_len.saturating_sub(data_offset as usize);
+
+        self.cell_buffer.truncate(mem::size_of::<BaseBlock>());
+        self.reader.seek(SeekFrom::Start(file_offset as u64))?;
+
+        // Never read beyond the available hive data, no matter what the cell size says.
+        // This bounds the allocation, and the checks of `cell_range_from_data_offset` report
+        // a cell exceeding the hive data just like for a `Hive` in memory.
+        let header_len = remaining.min(mem::size_of::<CellHeader>());
+        (&mut self.reader)
+            .take(header_len as u64)
+            .read_to_end(&mut self.cell_buffer)?;
+
+        if header_len == mem::size_of::<CellHeader>() {
+            let header_bytes = &self.cell_buffer[mem::size_of::<BaseBlock>()..];
+            let cell_size = i32::from_le_bytes(header_bytes.try_into().unwrap());
+
+            // A cell with size > 0 is unallocated and rejected without reading it.
+            if cell_size <= 0 {
+                let cell_len = (cell_size.unsigned_abs() as usize).min(remaining);
+                (&mut self.reader)
+                    .take((cell_len.saturating_sub(header_len)) as u64)
+                    .read_to_end(&mut self.cell_buffer)?;
+            }
+        }
+
+        let hive = Hive::from_single_cell(self.cell_buffer.as_slice(), data_offset)?;
+        let cell_range = hive.cell_range_from_data_offset(0)?;
+
+        Ok((hive, cell_range))
+    }
+
+    /// Reads the Key Node at `data_offset` and returns its name along with a [`KeyFrame`]
+    /// of its values and subkeys.
+    fn read_key_node(&mut self, data_offset: u32) -> Result<(String, KeyFrame)> {
+        if !self.visited.insert(data_offset) {
+            return Err(NtHiveError::KeyNodeCycle {
+                offset: mem::size_of::<BaseBlock>() + data_offset as usize,
+            });
+        }
+
+        let (
+            key_name,
+            subkey_count,
+            subkeys_list_offset,
+            key_values_list_offset,
+            key_values_count,
+            count_field_offset,
+        ) = {
+            let (hive, cell_range) = self.read_cell(data_offset)?;
+            let key_node = KeyNode::from_cell_range(&hive, cell_range)?;
+            let key_name = key_node.name()?.to_string_lossy();
+            let header = key_node.header();
+
+            (
+                key_name,
+                header.subkey_count.get(),
+                header.subkeys_list_offset.get(),
+                header.key_values_list_offset.get(),
+                header.key_values_count.get(),
+                hive.offset_of_field(&header.key_values_count),
+            )
+        };
+
+        let subkey_offsets = if subkeys_list_offset == u32::MAX || subkey_count == 0 {
+            // This Key Node has no subkeys.
+            Vec::new()
+        } else {
+            self.read_subkey_offsets(subkeys_list_offset, true)?
+        };
+
+        let key_value_offsets = if key_values_list_offset == u32::MAX || key_values_count == 0 {
+            // This Key Node has no values.
+            Vec::new()
+        } else {
+            self.read_key_value_offsets(
+                key_values_list_offset,
+                key_values_count,
+                count_field_offset,
+            )?
+        };
+
+        let key_frame = KeyFrame {
+            key_value_offsets: key_value_offsets.into_iter(),
+            subkey_offsets: subkey_offsets.into_iter(),
+        };
+
+        Ok((key_name, key_frame))
+    }
+
+    /// Reads the Key Value at `data_offset` and returns the corresponding [`Event::Value`].
+    fn read_key_value(&mut self, data_offset: u32) -> Result<Event> {
+        let (hive, cell_range) = self.read_cell(data_offset)?;
+        let key_value = KeyValue::new(&hive, cell_range)?;
+
+        Ok(Event::Value {
+            name: key_value.name()?.to_string_lossy(),
+            data_type: key_value.data_type()?,
+            data_len: key_value.data_size(),
+        })
+    }
+
+    /// Reads the Key Values List at `data_offset` and returns the offsets of its Key Values.
+    fn read_key_value_offsets(
+        &mut self,
+        data_offset: u32,
+        count: u32,
+        count_field_offset: usize,
+    ) -> Result<Vec<u32>> {
+        let (hive, cell_range) = self.read_cell(data_offset)?;
+        let offsets = KeyValuesListItemRanges::new(count, count_field_offset, cell_range)?
+            .map(|item_range| item_range.key_value_offset(&hive))
+            .collect();
+
+        Ok(offsets)
+    }
+
+    /// Reads the Subkeys List at `data_offset` and returns the offsets of its Key Nodes.
+    /// The Leafs of an Index Root are resolved as well.
+    fn read_subkey_offsets(
+        &mut self,
+        data_offset: u32,
+        index_root_supported: bool,
+    ) -> Result<Vec<u32>> {
+        let leaf_offsets = {
+            let (hive, cell_range) = self.read_cell(data_offset)?;
+            let subkeys_list = if index_root_supported {
+                SubkeysList::new(&hive, cell_range)?
+            } else {
+                SubkeysList::new_without_index_root(&hive, cell_range)?
+            };
+            let header = subkeys_list.header();
+            let count = header.count.get();
+            let count_field_offset = hive.offset_of_field(&header.count);
+            let data_range = subkeys_list.data_range.clone();
+
+            if let Some(leaf_type) = LeafType::from_signature(&header.signature) {
+                // Fast Leaf, Hash Leaf or Index Leaf
+                let key_node_offsets =
+                    LeafItemRanges::new(count, count_field_offset, data_range, leaf_type)?
+                        .map(|item_range| item_range.key_node_offset(&hive))
+                        .collect();
+                return Ok(key_node_offsets);
+            }
+
+            // Index Root
+            IndexRootItemRanges::new(count, count_field_offset, data_range)?
+                .map(|item_range| item_range.subkeys_list_offset(&hive))
+                .collect::<Vec<u32>>()
+        };
+
+        let mut key_node_offsets = Vec::new();
+        for leaf_offset in leaf_offsets {
+            key_node_offsets.extend(self.read_subkey_offsets(leaf_offset, false)?);
+        }
+
+        Ok(key_node_offsets)
+    }
+
+    fn next_event(&mut self) -> Option<Result<Event>> {
+        if !self.started {
+            self.started = true;
+
+            let (key_name, key_frame) = iter_try!(self.read_key_node(self.root_cell_offset));
+            self.stack.push(key_frame);
+            return Some(Ok(Event::EnterKey(key_name)));
+        }
+
+        let key_frame = self.stack.last_mut()?;
+
+        if let Some(key_value_offset) = key_frame.key_value_offsets.next() {
+            return Some(self.read_key_value(key_value_offset));
+        }
+
+        if let Some(subkey_offset) = key_frame.subkey_offsets.next() {
+            let (key_name, key_frame) = iter_try!(self.read_key_node(subkey_offset));
+            self.stack.push(key_frame);
+            return Some(Ok(Event::EnterKey(key_name)));
+        }
+
+        self.stack.pop();
+        Some(Ok(Event::LeaveKey))
+    }
+}
+
+impl<R> Iterator for HiveReader<R>
+where
+    R: Read + Seek,
+{
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let event = self.next_event();
+
+        // Stop after the last event or the first error, as there is no way to continue.
+        if !matches!(event, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        event
+    }
+}
+
+impl<R> core::iter::FusedIterator for HiveReader<R> where R: Read + Seek {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::*;
+
+    fn collect_events<B>(key_node: &KeyNode<B>, events: &mut Vec<Event>)
+    where
+        B: zerocopy::SplitByteSlice,
+    {
+        events.push(Event::EnterKey(key_node.name().unwrap().to_string_lossy()));
+
+        if let Some(key_values) = key_node.values() {
+            for key_value in key_values.unwrap() {
+                let key_value = key_value.unwrap();
+                events.push(Event::Value {
+                    name: key_value.name().unwrap().to_string_lossy(),
+                    data_type: key_value.data_type().unwrap(),
+                    data_len: key_value.data_size(),
+                });
+            }
+        }
+
+        if let Some(subkeys) = key_node.subkeys() {
+            for subkey in subkeys.unwrap() {
+                collect_events(&subkey.unwrap(), events);
+            }
+        }
+
+        events.push(Event::LeaveKey);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let (key_offset, parent_key_offset) = {
+            let hive = Hive::new(testhive.as_ref()).unwrap();
+            let parent_key_node = hive
+                .root_key_node()
+                .unwrap()
+                .subpath("subpath-test\\with-single-level-subkey")
+                .unwrap()
+                .unwrap();
+            let key_node = parent_key_node.subkey("subkey").unwrap().unwrap();
+            (key_node.offset(), parent_key_node.offset())
+        };
+
+        // Let the key without subkeys use the subkeys list of its parent, which contains the key
+        // itself.
+        let header_offset = 4096 + key_offset as usize + 4;
+        let field_offset =
+            header_offset + memoffset::offset_of!(crate::key_node::KeyNodeHeader, subkey_count);
+        testhive[field_offset..field_offset + 4].copy_from_slice(&1u32.to_le_bytes());
+        let list_offset_offset =
+            memoffset::offset_of!(crate::key_node::KeyNodeHeader, subkeys_list_offset);
+        let parent_field_offset = 4096 + parent_key_offset as usize + 4 + list_offset_offset;
+        let field_offset = header_offset + list_offset_offset;
+        testhive.copy_within(parent_field_offset..parent_field_offset + 4, field_offset);
+
+        let mut reader = HiveReader::new(Cursor::new(&testhive)).unwrap();
+        let error = reader.by_ref().find_map(|event| event.err());
+        assert_eq!(
+            error,
+            Some(NtHiveError::KeyNodeCycle {
+                offset: 4096 + key_offset as usize,
+            })
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_events() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let mut expected_events = Vec::new();
+        collect_events(&hive.root_key_node().unwrap(), &mut expected_events);
+
+        let reader = HiveReader::new(Cursor::new(&testhive)).unwrap();
+        let events = reader.collect::<Result<Vec<Event>>>().unwrap();
+        assert_eq!(events, expected_events);
+
+        assert!(events.contains(&Event::Value {
+            name: "binary".to_owned(),
+            data_type: KeyValueDataType::RegBinary,
+            data_len: 5,
+        }));
+        assert_eq!(events.first(), Some(&Event::EnterKey("ROOT".to_owned())));
+        assert_eq!(events.last(), Some(&Event::LeaveKey));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let testhive = crate::helpers::tests::testhive_vec();
+
+        // A truncated stream results in an I/O error.
+        let reader = HiveReader::new(Cursor::new(&testhive[..100]));
+        assert!(matches!(reader, Err(NtHiveError::Io { .. })));
+
+        // A corrupted base block is rejected.
+        let mut corrupted = testhive.clone();
+        corrupted[0] = b'x';
+        let reader = HiveReader::new(Cursor::new(&corrupted));
+        assert!(matches!(
+            reader,
+            Err(NtHiveError::InvalidFourByteSignature { .. })
+        ));
+
+        // A huge cell size is checked against the hive data before reading the cell.
+        let mut corrupted = testhive.clone();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_cell_offset = 4096 + hive.base_block().root_cell_offset() as usize;
+        corrupted[root_cell_offset..root_cell_offset + 4]
+            .copy_from_slice(&(-0x7fff_fff8i32).to_le_bytes());
+        let mut reader = HiveReader::new(Cursor::new(&corrupted)).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(NtHiveError::InvalidSizeField {
+                expected: 0x7fff_fff4,
+                ..
+            }))
+        ));
+
+        // A garbage subkeys list offset is ignored if the key has no subkeys.
+        let mut corrupted = testhive.clone();
+        let key_node = hive
+            .root_key_node()
+            .unwrap()
+            .subpath("subpath-test\\with-single-level-subkey\\subkey")
+            .unwrap()
+            .unwrap();
+        let field_offset = 4096
+            + key_node.offset() as usize
+            + 4
+            + memoffset::offset_of!(crate::key_node::KeyNodeHeader, subkeys_list_offset);
+        corrupted[field_offset..field_offset + 4].copy_from_slice(&0xdead_beefu32.to_le_bytes());
+        let reader = HiveReader::new(Cursor::new(&corrupted)).unwrap();
+        let events = reader.collect::<Result<Vec<Event>>>().unwrap();
+        let expected_events = HiveReader::new(Cursor::new(&testhive))
+            .unwrap()
+            .collect::<Result<Vec<Event>>>()
+            .unwrap();
+        assert_eq!(events, expected_events);
+
+        // An error stops the iteration.
+        let truncated = &testhive[..4096 + 32];
+        let mut reader = HiveReader::new(Cursor::new(truncated)).unwrap();
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.next().is_none());
+    }
+}
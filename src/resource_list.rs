@@ -0,0 +1,490 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use core::iter::FusedIterator;
+use core::mem;
+
+use zerocopy::byteorder::LittleEndian;
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout, Ref, SplitByteSlice, Unaligned, U16, U32,
+};
+
+use crate::error::{NtHiveError, Result};
+use crate::hive::Hive;
+
+/// Resource type of a [`CmPartialResourceDescriptor`] that is followed by device-specific data
+/// (`CmResourceTypeDeviceSpecific`).
+pub const CM_RESOURCE_TYPE_DEVICE_SPECIFIC: u8 = 5;
+
+/// Size of a `CM_PARTIAL_RESOURCE_DESCRIPTOR` written by 64-bit Windows.
+/// Its union contains a pointer-sized `KAFFINITY` for interrupts.
+const PARTIAL_RESOURCE_DESCRIPTOR_SIZE_64: usize = 20;
+
+/// Size of a `CM_PARTIAL_RESOURCE_DESCRIPTOR` written by 32-bit Windows.
+const PARTIAL_RESOURCE_DESCRIPTOR_SIZE_32: usize = 16;
+
+/// On-Disk Structure of a `CM_RESOURCE_LIST` header.
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+struct CmResourceListHeader {
+    count: U32<LittleEndian>,
+}
+
+/// On-Disk Structure of a `CM_FULL_RESOURCE_DESCRIPTOR` header,
+/// including the header of its `CM_PARTIAL_RESOURCE_LIST`.
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+struct CmFullResourceDescriptorHeader {
+    interface_type: U32<LittleEndian>,
+    bus_number: U32<LittleEndian>,
+    version: U16<LittleEndian>,
+    revision: U16<LittleEndian>,
+    count: U32<LittleEndian>,
+}
+
+/// On-Disk Structure of a `CM_PARTIAL_RESOURCE_DESCRIPTOR` header.
+/// It is followed by a union whose size depends on the architecture.
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+struct CmPartialResourceDescriptorHeader {
+    resource_type: u8,
+    share_disposition: u8,
+    flags: U16<LittleEndian>,
+}
+
+/// Returns the number of bytes that a `CM_RESOURCE_LIST` in `data` requires when its partial
+/// descriptors have `partial_size` bytes.
+///
+/// If `data` is too short, the returned size exceeds `data.len()`.
+fn required_size(data: &[u8], partial_size: usize) -> usize {
+    let Ok((header, _)) = Ref::<&[u8], CmResourceListHeader>::from_prefix(data) else {
+        return mem::size_of::<CmResourceListHeader>();
+    };
+
+    let mut offset = mem::size_of::<CmResourceListHeader>();
+
+    for _ in 0..header.count.get() {
+        let Ok((full_header, _)) =
+            Ref::<&[u8], CmFullResourceDescriptorHeader>::from_prefix(&data[offset..])
+        else {
+            return offset + mem::size_of::<CmFullResourceDescriptorHeader>();
+        };
+        offset += mem::size_of::<CmFullResourceDescriptorHeader>();
+
+        for _ in 0..full_header.count.get() {
+            let Some(partial) = data.get(offset..offset.saturating_add(partial_size)) else {
+                return offset.saturating_add(partial_size);
+            };
+            offset = offset
+                .saturating_add(partial_size)
+                .saturating_add(device_specific_data_size(partial));
+        }
+
+        if offset > data.len() {
+            return offset;
+        }
+    }
+
+    offset
+}
+
+/// Returns the number of device-specific data bytes following the given partial descriptor.
+fn device_specific_data_size(partial: &[u8]) -> usize {
+    if partial[0] == CM_RESOURCE_TYPE_DEVICE_SPECIFIC {
+        // The union begins with the `DataSize` field of `DeviceSpecificData`.
+        let data_size_offset = mem::size_of::<CmPartialResourceDescriptorHeader>();
+        let data_size_bytes = &partial[data_size_offset..data_size_offset + 4];
+        u32::from_le_bytes(data_size_bytes.try_into().unwrap()) as usize
+    } else {
+        0
+    }
+}
+
+/// Zero-copy representation of `REG_RESOURCE_LIST` data (`CM_RESOURCE_LIST`),
+/// returned by [`KeyValue::resource_list`].
+///
+/// It consists of a list of [`CmFullResourceDescriptor`]s, each of them holding a list of
+/// [`CmPartialResourceDescriptor`]s.
+/// Partial descriptors written by 32-bit and 64-bit Windows differ in size.
+/// The layout is detected when parsing and the entire structure is validated upfront,
+/// so that accessing it never fails.
+///
+/// [`KeyValue::resource_list`]: crate::key_value::KeyValue::resource_list
+#[derive(Clone, Debug)]
+pub struct CmResourceList<'h> {
+    data: &'h [u8],
+    partial_size: usize,
+}
+
+impl<'h> CmResourceList<'h> {
+    pub(crate) fn new<B>(hive: &Hive<B>, data: &'h [u8]) -> Result<Self>
+    where
+        B: SplitByteSlice,
+    {
+        // Prefer the 64-bit layout, as it is far more common nowadays.
+        let required_size_64 = required_size(data, PARTIAL_RESOURCE_DESCRIPTOR_SIZE_64);
+        let partial_size = if required_size_64 == data.len() {
+            PARTIAL_RESOURCE_DESCRIPTOR_SIZE_64
+        } else if required_size(data, PARTIAL_RESOURCE_DESCRIPTOR_SIZE_32) == data.len() {
+            PARTIAL_RESOURCE_DESCRIPTOR_SIZE_32
+        } else {
+            return Err(NtHiveError::InvalidDataSize {
+                context: "resource list",
+                offset: hive.offset_of_field(data),
+                expected: required_size_64,
+                actual: data.len(),
+            });
+        };
+
+        Ok(Self { data, partial_size })
+    }
+
+    /// Returns the number of full resource descriptors in this list.
+    pub fn count(&self) -> u32 {
+        self.header().count.get()
+    }
+
+    /// Returns an iterator over the full resource descriptors in this list.
+    pub fn full_descriptors(&self) -> CmFullResourceDescriptors<'h> {
+        CmFullResourceDescriptors {
+            remaining: &self.data[mem::size_of::<CmResourceListHeader>()..],
+            count_left: self.count(),
+            partial_size: self.partial_size,
+        }
+    }
+
+    /// Returns `true` if this list uses the layout of 64-bit Windows.
+    pub fn is_64bit(&self) -> bool {
+        self.partial_size == PARTIAL_RESOURCE_DESCRIPTOR_SIZE_64
+    }
+
+    fn header(&self) -> Ref<&'h [u8], CmResourceListHeader> {
+        Ref::from_prefix(self.data).unwrap().0
+    }
+}
+
+/// Iterator over
+///   the full resource descriptors of a [`CmResourceList`],
+///   returning a [`CmFullResourceDescriptor`] for each item.
+#[derive(Clone, Debug)]
+pub struct CmFullResourceDescriptors<'h> {
+    remaining: &'h [u8],
+    count_left: u32,
+    partial_size: usize,
+}
+
+impl<'h> Iterator for CmFullResourceDescriptors<'h> {
+    type Item = CmFullResourceDescriptor<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count_left == 0 {
+            return None;
+        }
+
+        // `CmResourceList::new` has validated the sizes, so the following operations must succeed.
+        let (header, partials) =
+            Ref::<&[u8], CmFullResourceDescriptorHeader>::from_prefix(self.remaining).unwrap();
+        let mut partials_size = 0;
+
+        for _ in 0..header.count.get() {
+            let partial = &partials[partials_size..partials_size + self.partial_size];
+            partials_size += self.partial_size + device_specific_data_size(partial);
+        }
+
+        let (partials, remaining) = partials.split_at(partials_size);
+        self.remaining = remaining;
+        self.count_left -= 1;
+
+        Some(CmFullResourceDescriptor {
+            header,
+            partials,
+            partial_size: self.partial_size,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.count_left as usize;
+        (size, Some(size))
+    }
+}
+
+impl ExactSizeIterator for CmFullResourceDescriptors<'_> {}
+impl FusedIterator for CmFullResourceDescriptors<'_> {}
+
+/// A single full resource descriptor (`CM_FULL_RESOURCE_DESCRIPTOR`) of a [`CmResourceList`],
+/// describing the resources of a single bus.
+pub struct CmFullResourceDescriptor<'h> {
+    header: Ref<&'h [u8], CmFullResourceDescriptorHeader>,
+    partials: &'h [u8],
+    partial_size: usize,
+}
+
+impl<'h> CmFullResourceDescriptor<'h> {
+    /// Returns the bus number.
+    pub fn bus_number(&self) -> u32 {
+        self.header.bus_number.get()
+    }
+
+    /// Returns the bus type as an `INTERFACE_TYPE` value (e.g. 0 for `Internal`, 5 for `PCIBus`).
+    pub fn interface_type(&self) -> u32 {
+        self.header.interface_type.get()
+    }
+
+    /// Returns an iterator over the partial resource descriptors of this full descriptor.
+    pub fn partial_descriptors(&self) -> CmPartialResourceDescriptors<'h> {
+        CmPartialResourceDescriptors {
+            remaining: self.partials,
+            count_left: self.header.count.get(),
+            partial_size: self.partial_size,
+        }
+    }
+
+    /// Returns the revision of the partial resource list.
+    pub fn revision(&self) -> u16 {
+        self.header.revision.get()
+    }
+
+    /// Returns the version of the partial resource list.
+    pub fn version(&self) -> u16 {
+        self.header.version.get()
+    }
+}
+
+/// Iterator over
+///   the partial resource descriptors of a [`CmFullResourceDescriptor`],
+///   returning a [`CmPartialResourceDescriptor`] for each item.
+#[derive(Clone, Debug)]
+pub struct CmPartialResourceDescriptors<'h> {
+    remaining: &'h [u8],
+    count_left: u32,
+    partial_size: usize,
+}
+
+impl<'h> Iterator for CmPartialResourceDescriptors<'h> {
+    type Item = CmPartialResourceDescriptor<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count_left == 0 {
+            return None;
+        }
+
+        // `CmResourceList::new` has validated the sizes, so the following operations must succeed.
+        let (partial, remaining) = self.remaining.split_at(self.partial_size);
+        let (device_specific_data, remaining) =
+            remaining.split_at(device_specific_data_size(partial));
+        let (header, union_data) =
+            Ref::<&[u8], CmPartialResourceDescriptorHeader>::from_prefix(partial).unwrap();
+
+        self.remaining = remaining;
+        self.count_left -= 1;
+
+        Some(CmPartialResourceDescriptor {
+            header,
+            union_data,
+            device_specific_data,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.count_left as usize;
+        (size, Some(size))
+    }
+}
+
+impl ExactSizeIterator for CmPartialResourceDescriptors<'_> {}
+impl FusedIterator for CmPartialResourceDescriptors<'_> {}
+
+/// A single partial resource descriptor (`CM_PARTIAL_RESOURCE_DESCRIPTOR`),
+/// describing a single resource like a port, interrupt, or memory range.
+pub struct CmPartialResourceDescriptor<'h> {
+    header: Ref<&'h [u8], CmPartialResourceDescriptorHeader>,
+    union_data: &'h [u8],
+    device_specific_data: &'h [u8],
+}
+
+impl<'h> CmPartialResourceDescriptor<'h> {
+    /// Returns the device-specific data following this descriptor if its resource type is
+    /// [`CM_RESOURCE_TYPE_DEVICE_SPECIFIC`].
+    pub fn device_specific_data(&self) -> Option<&'h [u8]> {
+        (self.resource_type() == CM_RESOURCE_TYPE_DEVICE_SPECIFIC)
+            .then_some(self.device_specific_data)
+    }
+
+    /// Returns the resource-specific flags (`CM_RESOURCE_*`).
+    pub fn flags(&self) -> u16 {
+        self.header.flags.get()
+    }
+
+    /// Returns the raw bytes of the resource-type-specific union `u`.
+    ///
+    /// It is 12 bytes long for the 32-bit layout and 16 bytes long for the 64-bit layout.
+    pub fn raw_data(&self) -> &'h [u8] {
+        self.union_data
+    }
+
+    /// Returns the resource type (`CmResourceType*`), e.g. 1 for ports, 2 for interrupts,
+    /// and 3 for memory ranges.
+    pub fn resource_type(&self) -> u8 {
+        self.header.resource_type
+    }
+
+    /// Returns how this resource is shared (`CmResourceShare*`).
+    pub fn share_disposition(&self) -> u8 {
+        self.header.share_disposition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Builds a `CM_PARTIAL_RESOURCE_DESCRIPTOR` of the 64-bit layout for a memory range.
+    fn memory_descriptor(start: u64, length: u32) -> Vec<u8> {
+        let mut descriptor = vec![3, 1];
+        descriptor.extend_from_slice(&0u16.to_le_bytes());
+        descriptor.extend_from_slice(&start.to_le_bytes());
+        descriptor.extend_from_slice(&length.to_le_bytes());
+        descriptor.extend_from_slice(&[0; 4]);
+        descriptor
+    }
+
+    #[test]
+    fn test_resource_list() {
+        // This mirrors the `.Translated` value of
+        // `HARDWARE\RESOURCEMAP\System Resources\Physical Memory` on 64-bit Windows:
+        // One full descriptor for the internal bus with three memory ranges.
+        let memory_ranges = [
+            (0x1000u64, 0x9e000u32),
+            (0x100000, 0x7fe00000),
+            (0x1_0000_0000, 0x8000_0000),
+        ];
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&(memory_ranges.len() as u32).to_le_bytes());
+        for (start, length) in memory_ranges {
+            data.extend_from_slice(&memory_descriptor(start, length));
+        }
+
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                ".Translated",
+                KeyValueDataType::RegResourceList,
+                &data,
+            )
+            .set_value(
+                "data-test",
+                "truncated",
+                KeyValueDataType::RegResourceList,
+                &data[..data.len() - 1],
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+
+        let key_value = key_node.value(".Translated").unwrap().unwrap();
+        let resource_list = key_value.resource_list().unwrap();
+        assert_eq!(resource_list.count(), 1);
+        assert!(resource_list.is_64bit());
+
+        let mut full_descriptors = resource_list.full_descriptors();
+        assert_eq!(full_descriptors.len(), 1);
+        let full_descriptor = full_descriptors.next().unwrap();
+        assert!(full_descriptors.next().is_none());
+        assert_eq!(full_descriptor.interface_type(), 0);
+        assert_eq!(full_descriptor.bus_number(), 0);
+        assert_eq!(full_descriptor.version(), 1);
+        assert_eq!(full_descriptor.revision(), 1);
+
+        let partial_descriptors = full_descriptor.partial_descriptors();
+        assert_eq!(partial_descriptors.len(), memory_ranges.len());
+        for (partial_descriptor, (start, length)) in partial_descriptors.zip(memory_ranges) {
+            assert_eq!(partial_descriptor.resource_type(), 3);
+            assert_eq!(partial_descriptor.share_disposition(), 1);
+            assert_eq!(partial_descriptor.flags(), 0);
+            assert!(partial_descriptor.device_specific_data().is_none());
+
+            let raw_data = partial_descriptor.raw_data();
+            assert_eq!(raw_data.len(), 16);
+            assert_eq!(raw_data[..8], start.to_le_bytes());
+            assert_eq!(raw_data[8..12], length.to_le_bytes());
+        }
+
+        // Data that fits neither layout is rejected.
+        let key_value = key_node.value("truncated").unwrap().unwrap();
+        assert!(matches!(
+            key_value.resource_list(),
+            Err(NtHiveError::InvalidDataSize { .. })
+        ));
+
+        // Other data types are rejected.
+        let key_value = key_node.value("binary").unwrap().unwrap();
+        assert!(matches!(
+            key_value.resource_list(),
+            Err(NtHiveError::InvalidKeyValueDataType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resource_list_32bit() {
+        // A single full descriptor with a port range and device-specific data, as written by
+        // 32-bit Windows.
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[1, 1, 0x05, 0]);
+        data.extend_from_slice(&0x3f8u64.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&[CM_RESOURCE_TYPE_DEVICE_SPECIFIC, 0, 0, 0]);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let patched = HivePatch::new()
+            .set_value(
+                "data-test",
+                "resources",
+                KeyValueDataType::RegResourceList,
+                &data,
+            )
+            .apply(&hive)
+            .unwrap();
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let key_value = key_node.value("resources").unwrap().unwrap();
+
+        let resource_list = key_value.resource_list().unwrap();
+        assert!(!resource_list.is_64bit());
+
+        let full_descriptor = resource_list.full_descriptors().next().unwrap();
+        assert_eq!(full_descriptor.interface_type(), 5);
+        assert_eq!(full_descriptor.bus_number(), 2);
+
+        let mut partial_descriptors = full_descriptor.partial_descriptors();
+        let port = partial_descriptors.next().unwrap();
+        assert_eq!(port.resource_type(), 1);
+        assert_eq!(port.flags(), 5);
+        assert_eq!(port.raw_data().len(), 12);
+        assert!(port.device_specific_data().is_none());
+
+        let device_specific = partial_descriptors.next().unwrap();
+        assert_eq!(
+            device_specific.device_specific_data(),
+            Some(&[0xaa, 0xbb, 0xcc][..])
+        );
+        assert!(partial_descriptors.next().is_none());
+    }
+}
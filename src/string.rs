@@ -4,9 +4,10 @@
 use core::char;
 use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
 
 #[cfg(feature = "alloc")]
-use alloc::string::String;
+use {alloc::string::String, alloc::vec::Vec, core::fmt::Write};
 
 /// Sorted table of lowercase Basic Multilingual Plane (BMP) character code points and their uppercase equivalents.
 /// This is what Windows registry hives use to perform case-insensitive comparisons.
@@ -1206,20 +1207,91 @@ static BMP_UPPERCASE_TABLE: &[(u16, u16)] = &[
     (0xff5a, 0xff3a),
 ];
 
-fn utf16_code_unit_to_uppercase(unit: u16) -> u16 {
+/// Joins the given names into a `String`, separated by `sep`.
+///
+/// Names are decoded lossily like [`NtHiveNameString::to_string_lossy`] does.
+/// This is useful for building a path from the names of several keys.
+#[cfg(feature = "alloc")]
+pub fn join_names<'h, I>(parts: I, sep: char) -> String
+where
+    I: IntoIterator<Item = NtHiveNameString<'h>>,
+{
+    let mut string = String::new();
+
+    for (i, part) in parts.into_iter().enumerate() {
+        if i > 0 {
+            string.push(sep);
+        }
+
+        // Writing to a `String` never fails.
+        write!(string, "{part}").unwrap();
+    }
+
+    string
+}
+
+/// Writes `s` to `out`, escaped for use inside a quoted string of a `.reg` file
+/// (`Windows Registry Editor Version 5.00`).
+///
+/// Backslashes and quotation marks are escaped with a backslash.
+/// Line feeds, carriage returns, and NUL characters become `\n`, `\r`, and `\0` respectively,
+/// so that the string stays on a single line and isn't cut off at a NUL character.
+/// All other characters are written unchanged.
+pub fn reg_escape<W: fmt::Write>(s: &str, out: &mut W) -> fmt::Result {
+    for single_char in s.chars() {
+        match single_char {
+            '\\' => out.write_str("\\\\")?,
+            '"' => out.write_str("\\\"")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\0' => out.write_str("\\0")?,
+            _ => out.write_char(single_char)?,
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn utf16_code_unit_to_uppercase(unit: u16) -> u16 {
     match BMP_UPPERCASE_TABLE.binary_search_by(|&(key, _)| key.cmp(&unit)) {
         Ok(index) => BMP_UPPERCASE_TABLE[index].1,
         Err(_) => unit,
     }
 }
 
+/// Inverts [`utf16_code_unit_to_uppercase`].
+///
+/// Where several code units have the same uppercase equivalent, the lowercase mapping of the
+/// Unicode Standard is preferred (e.g. U+039C GREEK CAPITAL LETTER MU becomes U+03BC GREEK
+/// SMALL LETTER MU and not U+00B5 MICRO SIGN).
+#[cfg(feature = "alloc")]
+fn utf16_code_unit_to_lowercase(unit: u16) -> u16 {
+    if let Some(single_char) = char::from_u32(unit as u32) {
+        let mut lowercase = single_char.to_lowercase();
+
+        if let (Some(lower_char), None) = (lowercase.next(), lowercase.next()) {
+            if let Ok(lower) = u16::try_from(lower_char as u32) {
+                if lower != unit && utf16_code_unit_to_uppercase(lower) == unit {
+                    return lower;
+                }
+            }
+        }
+    }
+
+    // The table is sorted by lowercase code units, so the first match has the lowest one.
+    BMP_UPPERCASE_TABLE
+        .iter()
+        .find(|&&(_, upper)| upper == unit)
+        .map_or(unit, |&(lower, _)| lower)
+}
+
 /// Zero-copy representation of a key name or value name string stored in hive data.
 /// Can be either in Latin1 (ISO-8859-1) or UTF-16 (Little-Endian).
 ///
 /// This allows to work with the string without performing any allocations or conversions.
 /// If the `alloc` feature is enabled, [`to_string_checked`](NtHiveNameString::to_string_checked) and
 /// [`to_string_lossy`](NtHiveNameString::to_string_lossy) can be used to to retrieve a `String`.
-#[derive(Clone, Debug, Eq)]
+#[derive(Clone, Eq)]
 pub enum NtHiveNameString<'h> {
     /// A byte stream where each byte is a single character of the Latin1 (ISO-8859-1)
     /// character set.
@@ -1231,7 +1303,7 @@ pub enum NtHiveNameString<'h> {
 }
 
 impl<'h> NtHiveNameString<'h> {
-    fn cmp_iter<TI, OI>(mut this_iter: TI, mut other_iter: OI) -> Ordering
+    pub(crate) fn cmp_iter<TI, OI>(mut this_iter: TI, mut other_iter: OI) -> Ordering
     where
         TI: Iterator<Item = u16>,
         OI: Iterator<Item = u16>,
@@ -1297,22 +1369,222 @@ impl<'h> NtHiveNameString<'h> {
         }
     }
 
+    /// Returns `self` without the dangling last byte of an odd-length UTF-16LE string,
+    /// along with a flag indicating whether such a byte has been removed.
+    pub(crate) fn without_dangling_byte(&self) -> (NtHiveNameString<'h>, bool) {
+        match self {
+            Self::Latin1(bytes) => (Self::Latin1(bytes), false),
+            Self::Utf16LE(bytes) => {
+                let even_length = bytes.len() & !1;
+                (
+                    Self::Utf16LE(&bytes[..even_length]),
+                    even_length != bytes.len(),
+                )
+            }
+        }
+    }
+
+    /// Returns the raw bytes of `self`, regardless of the encoding.
+    ///
+    /// Use [`NtHiveNameString::is_latin1`] or [`NtHiveNameString::is_utf16le`] to find out
+    /// how to interpret them.
+    pub const fn as_bytes(&self) -> &'h [u8] {
+        match self {
+            Self::Latin1(bytes) => bytes,
+            Self::Utf16LE(bytes) => bytes,
+        }
+    }
+
+    /// Returns an iterator over the decoded [`prim@char`]s of `self` and their byte offsets
+    /// in the backing slice.
+    ///
+    /// A UTF-16 surrogate pair is decoded into a single [`prim@char`] with the offset of its
+    /// first code unit.
+    /// Unpaired surrogates are replaced by [`char::REPLACEMENT_CHARACTER`].
+    /// A dangling last byte of an odd-length UTF-16LE string is ignored.
+    pub fn char_indices(&self) -> impl Iterator<Item = (usize, char)> + 'h {
+        let (bytes, unit_size): (&'h [u8], usize) = match self {
+            Self::Latin1(bytes) => (bytes, 1),
+            Self::Utf16LE(bytes) => (bytes, 2),
+        };
+
+        let code_units = bytes.chunks_exact(unit_size).map(|unit| match *unit {
+            [byte] => u16::from(byte),
+            [low, high] => u16::from_le_bytes([low, high]),
+            _ => unreachable!(),
+        });
+
+        char::decode_utf16(code_units).scan(0, move |offset, result| {
+            let single_char = result.unwrap_or(char::REPLACEMENT_CHARACTER);
+            let char_offset = *offset;
+
+            // An unpaired surrogate is a single code unit just like the replacement character.
+            *offset += single_char.len_utf16() * unit_size;
+
+            Some((char_offset, single_char))
+        })
+    }
+
+    /// Compares `self` and `other` case-sensitively, code unit by code unit.
+    ///
+    /// In contrast to the [`Ord`] implementation, this doesn't follow Windows semantics,
+    /// but is useful for finding exact matches (e.g. when diffing two hives).
+    /// Latin1 and UTF-16LE strings are compared by their UTF-16 code units, so the encoding
+    /// doesn't matter.
+    pub fn cmp_case_sensitive(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Latin1(_), Self::Latin1(_)) => self.latin1_iter().cmp(other.latin1_iter()),
+            (Self::Latin1(_), Self::Utf16LE(_)) => self.latin1_iter().cmp(other.utf16le_iter()),
+            (Self::Utf16LE(_), Self::Latin1(_)) => self.utf16le_iter().cmp(other.latin1_iter()),
+            (Self::Utf16LE(_), Self::Utf16LE(_)) => self.utf16le_iter().cmp(other.utf16le_iter()),
+        }
+    }
+
+    /// Returns an iterator over the decoded [`prim@char`]s of `self`, without any allocations.
+    ///
+    /// Latin1 bytes are casted to [`prim@char`]s, and UTF-16LE code units are decoded with
+    /// invalid sequences replaced by [`char::REPLACEMENT_CHARACTER`], just like the
+    /// [`Display`](fmt::Display) implementation does.
+    /// Use [`NtHiveNameString::char_indices`] if you also need the byte offsets.
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'h {
+        self.char_indices().map(|(_, single_char)| single_char)
+    }
+
+    /// Compares `self` case-insensitively with the characters returned by `chars`,
+    /// just like comparing with a `str`.
+    ///
+    /// This is useful for comparing with names that are assembled lazily.
+    pub fn cmp_chars<I>(&self, chars: I) -> Ordering
+    where
+        I: Iterator<Item = char>,
+    {
+        let other_iter = chars.flat_map(|single_char| {
+            let mut buf = [0u16; 2];
+            let len = single_char.encode_utf16(&mut buf).len();
+            buf.into_iter().take(len)
+        });
+
+        match self {
+            Self::Latin1(_) => Self::cmp_iter(self.latin1_iter(), other_iter),
+            Self::Utf16LE(_) => Self::cmp_iter(self.utf16le_iter(), other_iter),
+        }
+    }
+
+    /// Checks that `self` and `other` are an exact (case-sensitive) match.
+    ///
+    /// See [`NtHiveNameString::cmp_case_sensitive`] for details.
+    pub fn eq_case_sensitive(&self, other: &Self) -> bool {
+        self.cmp_case_sensitive(other) == Ordering::Equal
+    }
+
+    /// Creates a [`NtHiveNameString`] from bytes of the Latin1 (ISO-8859-1) character set.
+    pub const fn from_latin1(bytes: &'h [u8]) -> Self {
+        Self::Latin1(bytes)
+    }
+
+    /// Creates a [`NtHiveNameString`] from UTF-16 code points in little-endian order.
+    pub const fn from_utf16le(bytes: &'h [u8]) -> Self {
+        Self::Utf16LE(bytes)
+    }
+
     /// Returns `true` if `self` has a length of zero bytes.
     pub const fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns `true` if `self` is encoded in Latin1 (ISO-8859-1).
+    pub const fn is_latin1(&self) -> bool {
+        matches!(self, Self::Latin1(_))
+    }
+
+    /// Returns `true` if `self` is encoded in UTF-16 (Little-Endian).
+    pub const fn is_utf16le(&self) -> bool {
+        matches!(self, Self::Utf16LE(_))
+    }
+
     /// Returns the length of `self`.
     ///
     /// This length is in bytes, not characters! In other words,
     /// it may not be what a human considers the length of the string.
     pub const fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Converts `self` to an owned `String` with ROT13 applied to all ASCII letters.
+    ///
+    /// This decodes names obfuscated by ROT13, like the value names below the `UserAssist` key.
+    /// All other characters are passed through unchanged, and undecodable code units are replaced
+    /// like [`NtHiveNameString::to_string_lossy`] does.
+    #[cfg(feature = "alloc")]
+    pub fn rot13_decoded(&self) -> String {
+        self.chars()
+            .map(|single_char| match single_char {
+                'A'..='M' | 'a'..='m' => (single_char as u8 + 13) as char,
+                'N'..='Z' | 'n'..='z' => (single_char as u8 - 13) as char,
+                _ => single_char,
+            })
+            .collect()
+    }
+
+    /// Checks case-insensitively whether `self` begins with `prefix`, without any allocations.
+    ///
+    /// This uses the same comparison as the [`PartialEq`] implementations for `str`.
+    /// An empty prefix always matches, whereas a prefix longer than `self` never does.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        fn starts_with_iter<I>(mut code_units: I, prefix: &str) -> bool
+        where
+            I: Iterator<Item = u16>,
+        {
+            prefix.encode_utf16().all(|prefix_code_unit| {
+                code_units.next().is_some_and(|code_unit| {
+                    utf16_code_unit_to_uppercase(code_unit)
+                        == utf16_code_unit_to_uppercase(prefix_code_unit)
+                })
+            })
+        }
+
         match self {
-            Self::Latin1(bytes) => bytes.len(),
-            Self::Utf16LE(bytes) => bytes.len(),
+            Self::Latin1(_) => starts_with_iter(self.latin1_iter(), prefix),
+            Self::Utf16LE(_) => starts_with_iter(self.utf16le_iter(), prefix),
         }
     }
 
+    /// Returns a subslice of `self` without a leading UTF-16 byte order mark (BOM).
+    ///
+    /// Some tools wrote a BOM into key names, which Windows treats as a regular character.
+    /// Both byte orders (`FF FE` and `FE FF`) are recognized at the start of a UTF-16LE name.
+    /// Latin1 names are returned unchanged.
+    pub fn strip_bom(&self) -> NtHiveNameString<'h> {
+        match self {
+            Self::Latin1(bytes) => Self::Latin1(bytes),
+            Self::Utf16LE(bytes) => match bytes {
+                [0xff, 0xfe, rest @ ..] | [0xfe, 0xff, rest @ ..] => Self::Utf16LE(rest),
+                _ => Self::Utf16LE(bytes),
+            },
+        }
+    }
+
+    /// Converts `self` to an owned lowercase `String` for consistent display output.
+    ///
+    /// This inverts the uppercase mapping that Windows uses for case-insensitive comparisons,
+    /// so the result always compares equal to `self`.
+    /// It is only meant for normalizing output, comparisons never need it.
+    /// Undecodable code units are replaced like [`NtHiveNameString::to_string_lossy`] does.
+    #[cfg(feature = "alloc")]
+    pub fn to_lowercase_string(&self) -> String {
+        self.chars()
+            .map(|single_char| match u16::try_from(single_char as u32) {
+                Ok(unit) => {
+                    let lower = utf16_code_unit_to_lowercase(unit);
+
+                    // The lowercase mapping never yields a surrogate.
+                    char::from_u32(lower as u32).unwrap()
+                }
+                Err(_) => single_char,
+            })
+            .collect()
+    }
+
     /// Attempts to convert `self` to an owned `String`.
     /// Returns `Some(String)` if all characters could be converted successfully or `None` if a decoding error occurred.
     #[cfg(feature = "alloc")]
@@ -1331,38 +1603,159 @@ impl<'h> NtHiveNameString<'h> {
     /// Converts `self` to an owned `String`, replacing invalid data with the replacement character (U+FFFD).
     #[cfg(feature = "alloc")]
     pub fn to_string_lossy(&self) -> String {
+        self.to_string_with(|_| Some(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Converts `self` to an owned `String`, calling `on_error` for every code unit that cannot be decoded.
+    ///
+    /// `on_error` receives the offending UTF-16 code unit (an unpaired surrogate) and may return
+    /// a substitute character or `None` to skip it.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with<F>(&self, mut on_error: F) -> String
+    where
+        F: FnMut(u16) -> Option<char>,
+    {
         match self {
             Self::Latin1(bytes) => bytes.iter().map(|byte| *byte as char).collect(),
             Self::Utf16LE(_) => char::decode_utf16(self.utf16le_iter())
-                .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .filter_map(|x| {
+                    x.or_else(|e| on_error(e.unpaired_surrogate()).ok_or(()))
+                        .ok()
+                })
                 .collect(),
         }
     }
+
+    /// Converts `self` to a `Vec` of UTF-16 code units, e.g. for a `WCHAR` buffer of a Windows API.
+    ///
+    /// Latin1 bytes are widened to a single code unit each, UTF-16LE code units are passed through
+    /// without any validation.
+    /// A dangling last byte of an odd-length UTF-16LE string is ignored.
+    /// Use [`NtHiveNameString::to_utf16_vec_nul`] if the buffer needs a terminating NUL.
+    #[cfg(feature = "alloc")]
+    pub fn to_utf16_vec(&self) -> Vec<u16> {
+        let mut code_units = Vec::with_capacity(self.utf16_len() + 1);
+
+        match self {
+            Self::Latin1(_) => code_units.extend(self.latin1_iter()),
+            Self::Utf16LE(_) => code_units.extend(self.utf16le_iter()),
+        }
+
+        code_units
+    }
+
+    /// Like [`NtHiveNameString::to_utf16_vec`], but appends a terminating NUL code unit.
+    #[cfg(feature = "alloc")]
+    pub fn to_utf16_vec_nul(&self) -> Vec<u16> {
+        let mut code_units = self.to_utf16_vec();
+        code_units.push(0);
+        code_units
+    }
+
+    /// Returns a subslice of `self` with all trailing NUL characters removed.
+    ///
+    /// Some names are stored with a terminating NUL character, which shouldn't be part of the output.
+    pub fn trim_end_nul(&self) -> NtHiveNameString<'h> {
+        match self {
+            Self::Latin1(bytes) => {
+                let mut bytes = *bytes;
+                while let [rest @ .., 0] = bytes {
+                    bytes = rest;
+                }
+
+                Self::Latin1(bytes)
+            }
+            Self::Utf16LE(bytes) => {
+                let mut bytes = *bytes;
+                while let [rest @ .., 0, 0] = bytes {
+                    // Only remove complete UTF-16 code units.
+                    if bytes.len() % 2 != 0 {
+                        break;
+                    }
+
+                    bytes = rest;
+                }
+
+                Self::Utf16LE(bytes)
+            }
+        }
+    }
+
+    /// Returns the length of `self` in UTF-16 code units.
+    ///
+    /// This is the number of `WCHAR`s a buffer needs to hold `self` (without terminating NUL)
+    /// when passing it to a Windows API.
+    /// Every Latin1 byte corresponds to a single UTF-16 code unit, whereas characters outside
+    /// the Basic Multilingual Plane take up two code units (a surrogate pair).
+    /// A dangling last byte of an odd-length UTF-16LE string is ignored.
+    pub const fn utf16_len(&self) -> usize {
+        match self {
+            Self::Latin1(bytes) => bytes.len(),
+            Self::Utf16LE(bytes) => bytes.len() / 2,
+        }
+    }
 }
 
-impl fmt::Display for NtHiveNameString<'_> {
+impl fmt::Debug for NtHiveNameString<'_> {
+    /// Formats the encoding along with the (lossily) decoded string, e.g. `Utf16LE("Hello")`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Latin1(bytes) => {
+                f.write_str("Latin1(\"")?;
+
                 for byte in bytes.iter() {
                     let single_char = *byte as char;
-                    single_char.fmt(f)?;
+                    write!(f, "{}", single_char.escape_debug())?;
                 }
             }
             Self::Utf16LE(_) => {
+                f.write_str("Utf16LE(\"")?;
+
                 let utf16_iter = char::decode_utf16(self.utf16le_iter())
                     .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER));
 
                 for single_char in utf16_iter {
-                    single_char.fmt(f)?;
+                    write!(f, "{}", single_char.escape_debug())?;
                 }
             }
         }
 
+        f.write_str("\")")
+    }
+}
+
+impl Default for NtHiveNameString<'_> {
+    /// Returns an empty Latin1 string.
+    fn default() -> Self {
+        Self::Latin1(&[])
+    }
+}
+
+impl fmt::Display for NtHiveNameString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for single_char in self.chars() {
+            single_char.fmt(f)?;
+        }
+
         Ok(())
     }
 }
 
+impl Hash for NtHiveNameString<'_> {
+    /// Hashes the uppercased UTF-16 code units, so that names comparing equal (case-insensitively
+    /// and regardless of their encoding) also hash equal.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.utf16_len());
+
+        let mut hash_code_unit =
+            |code_unit| state.write_u16(utf16_code_unit_to_uppercase(code_unit));
+        match self {
+            Self::Latin1(_) => self.latin1_iter().for_each(&mut hash_code_unit),
+            Self::Utf16LE(_) => self.utf16le_iter().for_each(&mut hash_code_unit),
+        }
+    }
+}
+
 impl Ord for NtHiveNameString<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -1445,10 +1838,226 @@ impl<'h> PartialOrd<NtHiveNameString<'h>> for &str {
     }
 }
 
+/// Owned representation of a key name or value name, stored as UTF-16 code units.
+///
+/// Comparisons are case-insensitive just like for [`NtHiveNameString`], which makes this type
+/// suitable as a key of ordered collections (e.g. a `BTreeMap`).
+/// Create one from an [`NtHiveNameString`] or a `&str` via the respective `From` implementation.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, Eq)]
+pub struct OwnedNtHiveName(Vec<u16>);
+
+#[cfg(feature = "alloc")]
+impl OwnedNtHiveName {
+    /// Returns the UTF-16 code units of this name.
+    pub fn as_utf16(&self) -> &[u16] {
+        &self.0
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        char::decode_utf16(self.0.iter().copied()).map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for OwnedNtHiveName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OwnedNtHiveName(\"")?;
+
+        for single_char in self.chars() {
+            write!(f, "{}", single_char.escape_debug())?;
+        }
+
+        f.write_str("\")")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for OwnedNtHiveName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for single_char in self.chars() {
+            single_char.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&str> for OwnedNtHiveName {
+    fn from(string: &str) -> Self {
+        Self(string.encode_utf16().collect())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<NtHiveNameString<'_>> for OwnedNtHiveName {
+    fn from(name: NtHiveNameString<'_>) -> Self {
+        Self(name.to_utf16_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Ord for OwnedNtHiveName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        NtHiveNameString::cmp_iter(self.0.iter().copied(), other.0.iter().copied())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq for OwnedNtHiveName {
+    /// Checks that two names are a case-insensitive match
+    /// (according to Windows' definition of case-insensitivity).
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<str> for OwnedNtHiveName {
+    fn eq(&self, other: &str) -> bool {
+        NtHiveNameString::cmp_iter(self.0.iter().copied(), other.encode_utf16()) == Ordering::Equal
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<&str> for OwnedNtHiveName {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd for OwnedNtHiveName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_as_bytes() {
+        let name = NtHiveNameString::Latin1(b"Hello");
+        assert_eq!(name.as_bytes(), b"Hello");
+        assert!(name.is_latin1());
+        assert!(!name.is_utf16le());
+
+        let name = NtHiveNameString::Utf16LE(&[b'H', 0, b'i', 0]);
+        assert_eq!(name.as_bytes(), [b'H', 0, b'i', 0]);
+        assert!(!name.is_latin1());
+        assert!(name.is_utf16le());
+
+        assert!(NtHiveNameString::default().as_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let latin1 = NtHiveNameString::Latin1(b"Caf\xe9");
+        let utf16le = NtHiveNameString::Utf16LE(&[b'C', 0, b'a', 0, b'f', 0, 0xe9, 0]);
+        assert!(latin1.eq_case_sensitive(&utf16le));
+        assert!(utf16le.eq_case_sensitive(&latin1));
+        assert_eq!(latin1.cmp_case_sensitive(&utf16le), Ordering::Equal);
+
+        // Names differing only in case are equal by default, but not case-sensitively.
+        let upper = NtHiveNameString::Utf16LE(&[b'C', 0, b'A', 0, b'F', 0, 0xc9, 0]);
+        assert_eq!(latin1, upper);
+        assert!(!latin1.eq_case_sensitive(&upper));
+        assert_eq!(latin1.cmp_case_sensitive(&upper), Ordering::Greater);
+        assert_eq!(upper.cmp_case_sensitive(&latin1), Ordering::Less);
+
+        let prefix = NtHiveNameString::Latin1(b"Caf");
+        assert_eq!(prefix.cmp_case_sensitive(&utf16le), Ordering::Less);
+        assert!(!prefix.eq_case_sensitive(&latin1));
+    }
+
+    #[test]
+    fn test_char_indices() {
+        let name = NtHiveNameString::Latin1(b"Caf\xe9");
+        assert!(name
+            .char_indices()
+            .eq([(0, 'C'), (1, 'a'), (2, 'f'), (3, 'é')]));
+
+        // "A", U+10410 as a surrogate pair, "b", an unpaired surrogate, "c".
+        let name = NtHiveNameString::Utf16LE(&[
+            b'A', 0, 0x01, 0xd8, 0x10, 0xdc, b'b', 0, 0x00, 0xd8, b'c', 0,
+        ]);
+        assert!(name.char_indices().eq([
+            (0, 'A'),
+            (2, '\u{10410}'),
+            (6, 'b'),
+            (8, char::REPLACEMENT_CHARACTER),
+            (10, 'c')
+        ]));
+
+        assert_eq!(NtHiveNameString::default().char_indices().count(), 0);
+    }
+
+    #[test]
+    fn test_chars() {
+        let name = NtHiveNameString::Latin1(b"Caf\xe9");
+        assert!(name.chars().eq(['C', 'a', 'f', '\u{e9}']));
+
+        // "A", U+10410 as a surrogate pair, an unpaired surrogate, "c", and a dangling byte.
+        let name = NtHiveNameString::Utf16LE(&[
+            b'A', 0, 0x01, 0xd8, 0x10, 0xdc, 0x00, 0xd8, b'c', 0, b'x',
+        ]);
+        assert!(name
+            .chars()
+            .eq(['A', '\u{10410}', char::REPLACEMENT_CHARACTER, 'c']));
+
+        // The characters match the `Display` output.
+        assert_eq!(name.chars().collect::<String>(), format!("{name}"));
+
+        assert_eq!(NtHiveNameString::default().chars().count(), 0);
+    }
+
+    #[test]
+    fn test_cmp_chars() {
+        let name = NtHiveNameString::Latin1(b"Hello");
+        assert_eq!(name.cmp_chars("hello".chars()), Ordering::Equal);
+        assert_eq!(name.cmp_chars("HELLO".chars()), Ordering::Equal);
+        assert_eq!(name.cmp_chars("hell".chars()), Ordering::Greater);
+        assert_eq!(name.cmp_chars("hellp".chars()), Ordering::Less);
+
+        // Characters outside the Basic Multilingual Plane are compared as surrogate pairs.
+        let name = NtHiveNameString::Utf16LE(&[b'h', 0, 0x01, 0xd8, 0x10, 0xdc]);
+        assert_eq!(name.cmp_chars("H\u{10410}".chars()), Ordering::Equal);
+        assert_eq!(
+            name.cmp_chars(['h', '\u{10410}'].into_iter()),
+            name.partial_cmp("h\u{10410}").unwrap()
+        );
+        assert_eq!(name.cmp_chars("h".chars()), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_debug() {
+        let name = NtHiveNameString::Utf16LE(&[b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0]);
+        assert_eq!(format!("{name:?}"), "Utf16LE(\"Hello\")");
+        assert_eq!(format!("{name}"), "Hello");
+
+        let name = NtHiveNameString::Latin1(b"Caf\xe9 \"1\"");
+        assert_eq!(format!("{name:?}"), "Latin1(\"Café \\\"1\\\"\")");
+        assert_eq!(format!("{name}"), "Café \"1\"");
+    }
+
+    #[test]
+    fn test_default() {
+        let name = NtHiveNameString::default();
+        assert!(name.is_empty());
+        assert_eq!(name, NtHiveNameString::from_latin1(b""));
+        assert_eq!(name, NtHiveNameString::from_utf16le(&[]));
+        assert_eq!(name, "");
+
+        const HELLO: NtHiveNameString<'static> = NtHiveNameString::from_latin1(b"Hello");
+        assert_eq!(
+            HELLO,
+            NtHiveNameString::from_utf16le(&[b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0])
+        );
+    }
+
     #[test]
     fn test_eq() {
         assert_eq!(NtHiveNameString::Latin1(b"Hello"), "Hello");
@@ -1497,6 +2106,38 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashMap;
+
+        fn hash_of(name: &NtHiveNameString) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            name.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let latin1 = NtHiveNameString::Latin1(b"Caf\xe9");
+        let utf16le = NtHiveNameString::Utf16LE(&[b'c', 0, b'A', 0, b'F', 0, 0xc9, 0]);
+        assert_eq!(latin1, utf16le);
+        assert_eq!(hash_of(&latin1), hash_of(&utf16le));
+
+        // A dangling byte is ignored by both comparison and hashing.
+        let dangling = NtHiveNameString::Utf16LE(&[b'C', 0, b'a', 0, b'f', 0, 0xe9, 0, b'x']);
+        assert_eq!(latin1, dangling);
+        assert_eq!(hash_of(&latin1), hash_of(&dangling));
+
+        assert_ne!(
+            hash_of(&latin1),
+            hash_of(&NtHiveNameString::Latin1(b"Cafe"))
+        );
+
+        let mut map = HashMap::new();
+        map.insert(latin1, 1);
+        assert_eq!(map.get(&utf16le), Some(&1));
+    }
+
     #[test]
     fn test_is_empty() {
         assert!(NtHiveNameString::Latin1(b"").is_empty());
@@ -1507,6 +2148,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_join_names() {
+        let parts = [
+            NtHiveNameString::Latin1(b"ControlSet001"),
+            NtHiveNameString::Utf16LE(&[
+                b'S', 0, b'e', 0, b'r', 0, b'v', 0, b'i', 0, b'c', 0, b'e', 0, b's', 0,
+            ]),
+            NtHiveNameString::Latin1(b"Caf\xe9"),
+        ];
+        assert_eq!(join_names(parts, '\\'), "ControlSet001\\Services\\Café");
+
+        assert_eq!(
+            join_names([NtHiveNameString::Latin1(b"Single")], '\\'),
+            "Single"
+        );
+        assert_eq!(join_names([], '\\'), "");
+    }
+
     #[test]
     fn test_len() {
         assert_eq!(NtHiveNameString::Latin1(b"Hello").len(), 5);
@@ -1516,6 +2175,23 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_owned_name() {
+        let latin1 = OwnedNtHiveName::from(NtHiveNameString::Latin1(b"Value"));
+        let utf16 = OwnedNtHiveName::from(NtHiveNameString::Utf16LE(b"V\0A\0L\0U\0E\0"));
+        let string = OwnedNtHiveName::from("vaLUE");
+
+        assert_eq!(latin1, utf16);
+        assert_eq!(utf16, string);
+        assert_eq!(latin1, "VALUE");
+        let longer = OwnedNtHiveName::from("values");
+        assert!(latin1 < longer);
+        assert_eq!(latin1.as_utf16(), &[0x56, 0x61, 0x6c, 0x75, 0x65]);
+        assert_eq!(latin1.to_string(), "Value");
+        assert_eq!(format!("{utf16:?}"), "OwnedNtHiveName(\"VALUE\")");
+    }
+
     #[test]
     fn test_ord() {
         assert!(NtHiveNameString::Latin1(b"a") < "b");
@@ -1543,4 +2219,186 @@ mod tests {
             NtHiveNameString::Utf16LE(&gothic_bairkan) < NtHiveNameString::Utf16LE(&full_width_a)
         );
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_reg_escape() {
+        let escape = |s: &str| {
+            let mut escaped = String::new();
+            reg_escape(s, &mut escaped).unwrap();
+            escaped
+        };
+
+        assert_eq!(escape("plain text"), "plain text");
+        assert_eq!(escape(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape(r"C:\Windows\"), r"C:\\Windows\\");
+        assert_eq!(escape("line1\r\nline2\0"), r"line1\r\nline2\0");
+        assert_eq!(escape("Café ☕"), "Café ☕");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_rot13_decoded() {
+        let name = NtHiveNameString::Latin1(b"HRZR_EHACNGU:P:\\Jvaqbjf\\flfgrz32\\pzq.rkr");
+        assert_eq!(
+            name.rot13_decoded(),
+            "UEME_RUNPATH:C:\\Windows\\system32\\cmd.exe"
+        );
+
+        // Non-ASCII characters are passed through.
+        let name = NtHiveNameString::Utf16LE(&[b'N', 0, 0xe4, 0, b'm', 0, 0x01, 0xd8, 0x10, 0xdc]);
+        assert_eq!(name.rot13_decoded(), "A\u{e4}z\u{10410}");
+
+        // Applying ROT13 twice yields the original name.
+        let name = NtHiveNameString::Latin1(b"Hello, World!");
+        let decoded = name.rot13_decoded();
+        assert_eq!(
+            NtHiveNameString::Latin1(decoded.as_bytes()).rot13_decoded(),
+            "Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let name = NtHiveNameString::Latin1(b"Shell\\Open\\Command");
+        assert!(name.starts_with("shell\\open"));
+        assert!(name.starts_with("SHELL\\"));
+        assert!(name.starts_with("Shell\\Open\\Command"));
+        assert!(name.starts_with(""));
+        assert!(!name.starts_with("Shell\\Open\\Commands"));
+        assert!(!name.starts_with("Open"));
+
+        let name = NtHiveNameString::Utf16LE(&[0xe4, 0, b'B', 0, 0x01, 0xd8, 0x10, 0xdc]);
+        assert!(name.starts_with("\u{c4}b"));
+        assert!(name.starts_with("\u{e4}B\u{10410}"));
+        assert!(!name.starts_with("\u{e4}B\u{10410}x"));
+
+        assert!(NtHiveNameString::default().starts_with(""));
+        assert!(!NtHiveNameString::default().starts_with("a"));
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        let name = NtHiveNameString::Utf16LE(&[0xff, 0xfe, b'K', 0, b'e', 0, b'y', 0]);
+        let stripped = name.strip_bom();
+        assert!(matches!(
+            stripped,
+            NtHiveNameString::Utf16LE(&[b'K', 0, b'e', 0, b'y', 0])
+        ));
+        assert_eq!(stripped, "Key");
+        assert_ne!(name, "Key");
+
+        let name = NtHiveNameString::Utf16LE(&[0xfe, 0xff, b'K', 0]);
+        assert_eq!(name.strip_bom(), "K");
+
+        // Names without a BOM and Latin1 names are returned unchanged.
+        let name = NtHiveNameString::Utf16LE(&[b'K', 0, b'e', 0, b'y', 0]);
+        assert_eq!(name.strip_bom().len(), 6);
+
+        let name = NtHiveNameString::Latin1(&[0xff, 0xfe, b'K']);
+        assert_eq!(name.strip_bom().len(), 3);
+    }
+
+    #[test]
+    fn test_to_lowercase_string() {
+        let name = NtHiveNameString::Latin1(b"CurrentControlSet-\xc4\xd6\xdc");
+        assert_eq!(name.to_lowercase_string(), "currentcontrolset-äöü");
+
+        // "ΜΙΚΡΟ", "Ａ", U+10410 (outside the BMP, left as is).
+        let name_string = "ΜΙΚΡΟＡ\u{10410}";
+        let name_bytes = name_string
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        let name = NtHiveNameString::Utf16LE(&name_bytes);
+        let lowercase = name.to_lowercase_string();
+        assert_eq!(lowercase, "μικροａ\u{10410}");
+        assert_eq!(name, lowercase.as_str());
+    }
+
+    #[test]
+    fn test_to_string_with() {
+        // "A", an unpaired high surrogate, "B"
+        let name = NtHiveNameString::Utf16LE(&[b'A', 0, 0x00, 0xD8, b'B', 0]);
+        assert_eq!(name.to_string_lossy(), "A\u{FFFD}B");
+
+        let mut offending_code_units = Vec::new();
+        let string = name.to_string_with(|code_unit| {
+            offending_code_units.push(code_unit);
+            Some('?')
+        });
+        assert_eq!(string, "A?B");
+        assert_eq!(offending_code_units, [0xD800]);
+
+        let string = name.to_string_with(|_| None);
+        assert_eq!(string, "AB");
+
+        // Latin1 strings can always be decoded.
+        let name = NtHiveNameString::Latin1(b"\xC4");
+        assert_eq!(name.to_string_with(|_| unreachable!()), "Ä");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_to_utf16_vec() {
+        let name = NtHiveNameString::Latin1(b"Caf\xe9");
+        let code_units = name.to_utf16_vec();
+        assert_eq!(code_units, [0x43, 0x61, 0x66, 0xe9]);
+        assert_eq!(String::from_utf16(&code_units).unwrap(), "Café");
+
+        // Round-trip a UTF-16LE name including a surrogate pair.
+        let bytes = [b'A', 0, 0x01, 0xd8, 0x10, 0xdc];
+        let name = NtHiveNameString::Utf16LE(&bytes);
+        let code_units = name.to_utf16_vec();
+        assert_eq!(code_units.len(), name.utf16_len());
+
+        let round_tripped = code_units
+            .iter()
+            .flat_map(|code_unit| code_unit.to_le_bytes())
+            .collect::<Vec<u8>>();
+        assert_eq!(round_tripped, bytes);
+        assert_eq!(NtHiveNameString::Utf16LE(&round_tripped), name);
+
+        assert_eq!(name.to_utf16_vec_nul(), [0x41, 0xd801, 0xdc10, 0]);
+        assert_eq!(NtHiveNameString::default().to_utf16_vec_nul(), [0]);
+    }
+
+    #[test]
+    fn test_trim_end_nul() {
+        let name = NtHiveNameString::Latin1(b"Hello\0");
+        assert_eq!(name.trim_end_nul(), NtHiveNameString::Latin1(b"Hello"));
+        let name = NtHiveNameString::Latin1(b"\0");
+        assert!(name.trim_end_nul().is_empty());
+
+        let name = NtHiveNameString::Utf16LE(&[b'H', 0, b'i', 0, 0, 0]);
+        assert_eq!(
+            name.trim_end_nul(),
+            NtHiveNameString::Utf16LE(&[b'H', 0, b'i', 0])
+        );
+        assert_eq!(name.trim_end_nul().len(), 4);
+
+        // A trailing NUL byte that is part of a code unit must not be removed.
+        let name = NtHiveNameString::Utf16LE(&[0, 1]);
+        assert_eq!(name.trim_end_nul().len(), 2);
+        let name = NtHiveNameString::Utf16LE(&[0x00, 0x01, 0x00]);
+        assert_eq!(name.trim_end_nul().len(), 3);
+    }
+
+    #[test]
+    fn test_utf16_len() {
+        assert_eq!(NtHiveNameString::Latin1(b"Caf\xe9").utf16_len(), 4);
+        assert_eq!(NtHiveNameString::Latin1(b"").utf16_len(), 0);
+        assert_eq!(
+            NtHiveNameString::Utf16LE(&[b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0]).utf16_len(),
+            5
+        );
+
+        // U+10410 is a single character, but a surrogate pair of two code units.
+        let name = NtHiveNameString::Utf16LE(&[b'A', 0, 0x01, 0xd8, 0x10, 0xdc]);
+        assert_eq!(name.char_indices().count(), 2);
+        assert_eq!(name.utf16_len(), 3);
+
+        // A dangling byte is not a code unit.
+        assert_eq!(NtHiveNameString::Utf16LE(&[b'A', 0, b'B']).utf16_len(), 1);
+    }
 }
@@ -21,7 +21,7 @@ use crate::leaf::{LeafKeyNodes, LeafKeyNodesMut, LeafType};
 /// On-Disk Structure of a Subkeys List header.
 /// This is common for all subkey types (Fast Leaf, Hash Leaf, Index Leaf, Index Root).
 #[derive(FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned)]
-#[repr(packed)]
+#[repr(C, packed)]
 pub(crate) struct SubkeysListHeader {
     pub(crate) signature: [u8; 2],
     pub(crate) count: U16<LittleEndian>,
@@ -60,6 +60,7 @@ where
     ) -> Result<Self> {
         let header_range = byte_subrange(&cell_range, mem::size_of::<SubkeysListHeader>())
             .ok_or_else(|| NtHiveError::InvalidHeaderSize {
+                context: "subkeys list",
                 offset: hive.offset_of_data_offset(cell_range.start),
                 expected: mem::size_of::<SubkeysListHeader>(),
                 actual: cell_range.len(),
@@ -88,11 +89,7 @@ where
             b"lf" | b"lh" | b"li" => return Ok(()),
 
             // Index Root
-            b"ri" => {
-                if index_root_supported {
-                    return Ok(());
-                }
-            }
+            b"ri" if index_root_supported => return Ok(()),
 
             // Anything else
             _ => (),
@@ -105,6 +102,7 @@ where
         };
 
         Err(NtHiveError::InvalidTwoByteSignature {
+            context: "subkeys list",
             offset: self.hive.offset_of_field(&header.signature),
             expected: expected_signature,
             actual: header.signature,
@@ -242,10 +240,83 @@ where
         }
     }
 
-    pub fn next(&mut self) -> Option<Result<KeyNodeMut<B>>> {
+    pub fn next(&mut self) -> Option<Result<KeyNodeMut<'_, B>>> {
         match self {
             Self::IndexRoot(iter) => iter.next(),
             Self::Leaf(iter) => iter.next(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use memoffset::offset_of;
+
+    use super::SubkeysListHeader;
+    use crate::key_node::KeyNodeHeader;
+    use crate::*;
+
+    /// Sets the item count of the Subkeys List of the given key to zero and returns its signature.
+    fn empty_subkeys_list(testhive: &mut [u8], key_node_offset: u32) -> [u8; 2] {
+        let list_offset_offset =
+            4096 + key_node_offset as usize + 4 + offset_of!(KeyNodeHeader, subkeys_list_offset);
+        let list_offset = u32::from_le_bytes(
+            testhive[list_offset_offset..list_offset_offset + 4]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+
+        let signature_offset = 4096 + list_offset + 4 + offset_of!(SubkeysListHeader, signature);
+        let count_offset = 4096 + list_offset + 4 + offset_of!(SubkeysListHeader, count);
+        testhive[count_offset..count_offset + 2].copy_from_slice(&0u16.to_le_bytes());
+
+        testhive[signature_offset..signature_offset + 2]
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_empty_subkeys_lists() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let index_root_key_offset = root_key_node
+            .subkey("subkey-test")
+            .unwrap()
+            .unwrap()
+            .offset();
+        let leaf_key_offset = root_key_node
+            .subkey("subpath-test")
+            .unwrap()
+            .unwrap()
+            .offset();
+
+        // The 512 subkeys of "subkey-test" are managed by an Index Root,
+        // the three ones of "subpath-test" by a Hash Leaf.
+        assert_eq!(
+            &empty_subkeys_list(&mut testhive, index_root_key_offset),
+            b"ri"
+        );
+        assert_eq!(&empty_subkeys_list(&mut testhive, leaf_key_offset), b"lh");
+
+        // Empty lists are valid and must behave like keys without subkeys.
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        for (key_name, subkey_name) in [("subkey-test", "key0"), ("subpath-test", "no-subkeys")] {
+            let key_node = root_key_node.subkey(key_name).unwrap().unwrap();
+
+            let subkeys = key_node.subkeys().unwrap().unwrap();
+            assert_eq!(subkeys.count(), 0);
+
+            let mut subkeys = key_node.subkeys().unwrap().unwrap();
+            assert!(subkeys.next().is_none());
+            assert!(subkeys.next().is_none());
+            assert!(key_node.subkeys().unwrap().unwrap().last().is_none());
+            assert!(key_node.subkeys().unwrap().unwrap().nth(1).is_none());
+
+            assert!(key_node.subkey(subkey_name).is_none());
+            assert!(key_node.subkey("").is_none());
+        }
+    }
+}
@@ -0,0 +1,567 @@
+// Copyright 2026 Colin Finck <colin@reactos.org>
+// SPDX-License-Identifier: GPL-2.0-or-later
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use zerocopy::SplitByteSlice;
+
+use crate::error::{NtHiveError, Result};
+use crate::key_node::KeyNode;
+use crate::key_value::KeyValue;
+use crate::key_values_list::KeyValues;
+use crate::subkeys_list::SubKeyNodes;
+
+/// Controls how traversals like [`KeyNode::descendants`] and [`KeyNode::all_values`] handle
+/// errors in individual entries.
+///
+/// [`KeyNode::all_values`]: crate::key_node::KeyNode::all_values
+/// [`KeyNode::descendants`]: crate::key_node::KeyNode::descendants
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TraversalMode {
+    /// Return the first error and stop the traversal afterwards.
+    #[default]
+    Strict,
+    /// Skip every entry that cannot be parsed and count it.
+    /// The subkeys of a skipped Key Node are not visited.
+    Lenient,
+}
+
+impl TraversalMode {
+    /// Returns `None` if `result` is an error that shall be skipped, counting it in `skipped`.
+    /// Otherwise, returns `result` unchanged.
+    pub(crate) fn handle<T>(self, result: Result<T>, skipped: &mut usize) -> Option<Result<T>> {
+        match (self, result) {
+            (TraversalMode::Lenient, Err(_)) => {
+                *skipped += 1;
+                None
+            }
+            (_, result) => Some(result),
+        }
+    }
+}
+
+/// Data offsets of all Key Nodes visited by a traversal.
+///
+/// A Key Node must never be reached twice.
+/// Otherwise, a subkeys list points back to an ancestor (or to another branch) and a traversal
+/// would run forever.
+#[derive(Clone, Default)]
+pub(crate) struct VisitedKeyNodes(BTreeSet<u32>);
+
+impl VisitedKeyNodes {
+    /// Records `key_node` as visited or returns [`NtHiveError::KeyNodeCycle`] if it has been
+    /// visited before.
+    pub(crate) fn insert<B>(&mut self, key_node: &KeyNode<'_, B>) -> Result<()>
+    where
+        B: SplitByteSlice,
+    {
+        if self.0.insert(key_node.offset()) {
+            Ok(())
+        } else {
+            Err(NtHiveError::KeyNodeCycle {
+                offset: key_node.file_offset(),
+            })
+        }
+    }
+}
+
+/// Iterator over
+///   a Key Node and all of its descendants in depth-first order,
+///   returning each [`KeyNode`] along with its path relative to the starting Key Node.
+///
+/// Paths are only built if requested, otherwise they are empty.
+/// A Key Node reached a second time is reported as [`NtHiveError::KeyNodeCycle`].
+pub(crate) struct SubtreeKeys<'h, B: SplitByteSlice> {
+    start: Option<KeyNode<'h, B>>,
+    stack: Vec<(String, SubKeyNodes<'h, B>)>,
+    visited: VisitedKeyNodes,
+    mode: TraversalMode,
+    include_start: bool,
+    with_paths: bool,
+    skipped: usize,
+    finished: bool,
+}
+
+impl<'h, B> SubtreeKeys<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(
+        start: KeyNode<'h, B>,
+        mode: TraversalMode,
+        include_start: bool,
+        with_paths: bool,
+    ) -> Self {
+        Self {
+            start: Some(start),
+            stack: Vec::new(),
+            visited: VisitedKeyNodes::default(),
+            mode,
+            include_start,
+            with_paths,
+            skipped: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the number of entries skipped so far in [`TraversalMode::Lenient`].
+    pub(crate) fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    fn next_key_node(&mut self) -> Option<Result<(String, KeyNode<'h, B>)>> {
+        if let Some(key_node) = self.start.take() {
+            let item = self
+                .visited
+                .insert(&key_node)
+                .and_then(|()| self.push_subkeys(String::new(), key_node));
+            if self.include_start || item.is_err() {
+                return Some(item);
+            }
+        }
+
+        loop {
+            let (parent_path, subkeys) = self.stack.last_mut()?;
+
+            let Some(key_node) = subkeys.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let item = key_node.and_then(|key_node| {
+                self.visited.insert(&key_node)?;
+
+                let path = if !self.with_paths {
+                    String::new()
+                } else if parent_path.is_empty() {
+                    key_node.name()?.to_string_lossy()
+                } else {
+                    format!("{parent_path}\\{}", key_node.name()?)
+                };
+
+                Ok((path, key_node))
+            });
+
+            let Some(item) = self.mode.handle(item, &mut self.skipped) else {
+                continue;
+            };
+
+            return Some(item.and_then(|(path, key_node)| self.push_subkeys(path, key_node)));
+        }
+    }
+
+    /// Schedules the subkeys of `key_node` to be visited next.
+    fn push_subkeys(
+        &mut self,
+        path: String,
+        key_node: KeyNode<'h, B>,
+    ) -> Result<(String, KeyNode<'h, B>)> {
+        if let Some(subkeys) = key_node.subkeys() {
+            // A lenient traversal still returns a Key Node whose subkeys cannot be enumerated.
+            if let Some(subkeys) = self.mode.handle(subkeys, &mut self.skipped) {
+                self.stack.push((path.clone(), subkeys?));
+            }
+        }
+
+        Ok((path, key_node))
+    }
+}
+
+impl<'h, B> Iterator for SubtreeKeys<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<(String, KeyNode<'h, B>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_key_node();
+
+        // Stop after the last key or the first error, as the remaining keys may be unreachable.
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        item
+    }
+}
+
+impl<B> FusedIterator for SubtreeKeys<'_, B> where B: SplitByteSlice {}
+
+/// Iterator over
+///   all descendants of a Key Node in depth-first order,
+///   returning a constant [`KeyNode`] for each descendant.
+///
+/// Returned by [`KeyNode::descendants`].
+///
+/// [`KeyNode::descendants`]: crate::key_node::KeyNode::descendants
+pub struct Descendants<'h, B: SplitByteSlice> {
+    keys: SubtreeKeys<'h, B>,
+}
+
+impl<'h, B> Descendants<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(key_node: KeyNode<'h, B>, mode: TraversalMode) -> Self {
+        Self {
+            keys: SubtreeKeys::new(key_node, mode, false, false),
+        }
+    }
+
+    /// Returns the number of entries skipped so far in [`TraversalMode::Lenient`].
+    ///
+    /// Check this after the iteration to find out whether the traversal was complete.
+    pub fn skipped(&self) -> usize {
+        self.keys.skipped()
+    }
+}
+
+impl<'h, B> Iterator for Descendants<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<KeyNode<'h, B>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.keys.next()?;
+        Some(item.map(|(_, key_node)| key_node))
+    }
+}
+
+impl<B> FusedIterator for Descendants<'_, B> where B: SplitByteSlice {}
+
+/// Iterator over
+///   the values of a Key Node and all of its descendants in depth-first order,
+///   returning a constant [`KeyValue`] for each value.
+///
+/// Returned by [`KeyNode::all_values`].
+///
+/// [`KeyNode::all_values`]: crate::key_node::KeyNode::all_values
+pub struct AllValues<'h, B: SplitByteSlice> {
+    keys: SubtreeKeys<'h, B>,
+    values: Option<KeyValues<'h, B>>,
+    mode: TraversalMode,
+    skipped: usize,
+    finished: bool,
+}
+
+impl<'h, B> AllValues<'h, B>
+where
+    B: SplitByteSlice,
+{
+    pub(crate) fn new(key_node: KeyNode<'h, B>, mode: TraversalMode) -> Self {
+        Self {
+            keys: SubtreeKeys::new(key_node, mode, true, false),
+            values: None,
+            mode,
+            skipped: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the number of entries skipped so far in [`TraversalMode::Lenient`].
+    ///
+    /// This counts both skipped keys and skipped values.
+    /// Check this after the iteration to find out whether the traversal was complete.
+    pub fn skipped(&self) -> usize {
+        self.keys.skipped() + self.skipped
+    }
+
+    fn next_key_value(&mut self) -> Option<Result<KeyValue<'h, B>>> {
+        loop {
+            if let Some(values) = &mut self.values {
+                match values.next() {
+                    Some(item) => match self.mode.handle(item, &mut self.skipped) {
+                        Some(item) => return Some(item),
+                        None => continue,
+                    },
+                    None => self.values = None,
+                }
+            }
+
+            let (_, key_node) = iter_try!(self.keys.next()?);
+
+            if let Some(values) = key_node.values() {
+                if let Some(values) = self.mode.handle(values, &mut self.skipped) {
+                    self.values = Some(iter_try!(values));
+                }
+            }
+        }
+    }
+}
+
+impl<'h, B> Iterator for AllValues<'h, B>
+where
+    B: SplitByteSlice,
+{
+    type Item = Result<KeyValue<'h, B>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_key_value();
+
+        // Stop after the last value or the first error.
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        item
+    }
+}
+
+impl<B> FusedIterator for AllValues<'_, B> where B: SplitByteSlice {}
+
+/// Iterator over
+///   a Key Node and all of its descendants in depth-first order,
+///   returning a constant [`KeyNode`] for each key and reporting progress along the way.
+///
+/// The `progress` callback is invoked with the number of keys and values visited so far
+/// after every `every` keys.
+/// It is never invoked if `every` is zero.
+/// The iteration stops after the first error.
+///
+/// Used by [`Hive::for_each_key_with_progress`].
+///
+/// [`Hive::for_each_key_with_progress`]: crate::hive::Hive::for_each_key_with_progress
+pub struct ProgressTraversal<'h, B: SplitByteSlice, P> {
+    keys: SubtreeKeys<'h, B>,
+    every: usize,
+    progress: P,
+    keys_visited: usize,
+    values_visited: usize,
+    finished: bool,
+}
+
+impl<'h, B, P> ProgressTraversal<'h, B, P>
+where
+    B: SplitByteSlice,
+    P: FnMut(usize, usize),
+{
+    pub(crate) fn new(key_node: KeyNode<'h, B>, every: usize, progress: P) -> Self {
+        Self {
+            keys: SubtreeKeys::new(key_node, TraversalMode::Strict, true, false),
+            every,
+            progress,
+            keys_visited: 0,
+            values_visited: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the number of keys visited so far.
+    pub fn keys_visited(&self) -> usize {
+        self.keys_visited
+    }
+
+    /// Returns the number of values of all keys visited so far.
+    pub fn values_visited(&self) -> usize {
+        self.values_visited
+    }
+
+    fn visit(&mut self, key_node: &KeyNode<'h, B>) -> Result<()> {
+        let value_count = match key_node.values() {
+            Some(values) => values?.len(),
+            None => 0,
+        };
+
+        self.keys_visited += 1;
+        self.values_visited += value_count;
+
+        if self.every > 0 && self.keys_visited % self.every == 0 {
+            (self.progress)(self.keys_visited, self.values_visited);
+        }
+
+        Ok(())
+    }
+
+    fn next_key_node(&mut self) -> Option<Result<KeyNode<'h, B>>> {
+        let (_, key_node) = iter_try!(self.keys.next()?);
+        iter_try!(self.visit(&key_node));
+        Some(Ok(key_node))
+    }
+}
+
+impl<'h, B, P> Iterator for ProgressTraversal<'h, B, P>
+where
+    B: SplitByteSlice,
+    P: FnMut(usize, usize),
+{
+    type Item = Result<KeyNode<'h, B>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_key_node();
+
+        // Stop after the first error, as `visit` may have failed halfway through a key.
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        item
+    }
+}
+
+impl<B, P> FusedIterator for ProgressTraversal<'_, B, P>
+where
+    B: SplitByteSlice,
+    P: FnMut(usize, usize),
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_cycle() {
+        let mut testhive = crate::helpers::tests::testhive_vec();
+        let (key_count, key_offset, parent_key_offset) = {
+            let hive = Hive::new(testhive.as_ref()).unwrap();
+            let root_key_node = hive.root_key_node().unwrap();
+            let key_count = root_key_node.descendants(TraversalMode::Strict).count();
+            let parent_key_node = root_key_node
+                .subpath("subpath-test\\with-single-level-subkey")
+                .unwrap()
+                .unwrap();
+            let key_node = parent_key_node.subkey("subkey").unwrap().unwrap();
+            assert!(key_node.subkeys().is_none());
+            (key_count, key_node.offset(), parent_key_node.offset())
+        };
+
+        // Let the key without subkeys use the subkeys list of its parent, which contains the key
+        // itself.
+        let header_offset = 4096 + key_offset as usize + 4;
+        let field_offset =
+            header_offset + memoffset::offset_of!(crate::key_node::KeyNodeHeader, subkey_count);
+        testhive[field_offset..field_offset + 4].copy_from_slice(&1u32.to_le_bytes());
+        let list_offset_offset =
+            memoffset::offset_of!(crate::key_node::KeyNodeHeader, subkeys_list_offset);
+        let parent_field_offset = 4096 + parent_key_offset as usize + 4 + list_offset_offset;
+        let field_offset = header_offset + list_offset_offset;
+        testhive.copy_within(parent_field_offset..parent_field_offset + 4, field_offset);
+
+        let expected_error = NtHiveError::KeyNodeCycle {
+            offset: 4096 + key_offset as usize,
+        };
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+        let key_node = hive.key_at(key_offset).unwrap();
+        assert!(key_node.subkey("subkey").unwrap().unwrap() == key_node);
+
+        // All traversals stop with an error instead of running forever.
+        let mut descendants = root_key_node.descendants(TraversalMode::Strict);
+        let error = descendants.by_ref().find_map(|key_node| key_node.err());
+        assert_eq!(error, Some(expected_error.clone()));
+        assert!(descendants.next().is_none());
+
+        let mut keys_breadth_first = hive.keys_breadth_first();
+        let error = keys_breadth_first
+            .by_ref()
+            .find_map(|key_node| key_node.err());
+        assert_eq!(error, Some(expected_error.clone()));
+        assert!(keys_breadth_first.next().is_none());
+
+        assert!(root_key_node
+            .all_values(TraversalMode::Strict)
+            .any(|key_value| key_value.is_err()));
+        assert_eq!(hive.validate_value_cells(), Err(expected_error.clone()));
+        assert_eq!(
+            hive.find_keys_named("subkey")
+                .find_map(|key_node| key_node.err()),
+            Some(expected_error)
+        );
+
+        // A lenient traversal skips the key reached a second time.
+        let mut descendants = root_key_node.descendants(TraversalMode::Lenient);
+        let lenient_key_count = descendants
+            .by_ref()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+            .len();
+        assert_eq!(lenient_key_count, key_count);
+        assert_eq!(descendants.skipped(), 1);
+    }
+
+    #[test]
+    fn test_traversal_modes() {
+        let testhive = crate::helpers::tests::testhive_vec();
+        let hive = Hive::new(testhive.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // Gather the numbers to expect from the intact hive.
+        let key_count = root_key_node.descendants(TraversalMode::Strict).count();
+        let value_count = root_key_node.all_values(TraversalMode::Strict).count();
+        let corrupt_key_node = root_key_node.subkey("subpath-test").unwrap().unwrap();
+        let corrupt_key_count = 1 + corrupt_key_node.descendants(TraversalMode::Strict).count();
+        let corrupt_value_count = corrupt_key_node.all_values(TraversalMode::Strict).count();
+        let data_key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        let data_value_count = data_key_node.values_sorted().unwrap().len();
+        let corrupt_key_value = data_key_node.value("dword").unwrap().unwrap();
+
+        // Corrupt the signatures of one subkey and one value.
+        let mut patched = testhive.clone();
+        let key_signature_offset = 4096 + corrupt_key_node.offset() as usize + 4;
+        patched[key_signature_offset..key_signature_offset + 2].copy_from_slice(b"xx");
+        let value_signature_offset = 4096 + corrupt_key_value.cell_offset() as usize + 4;
+        patched[value_signature_offset..value_signature_offset + 2].copy_from_slice(b"xx");
+
+        let hive = Hive::new(patched.as_ref()).unwrap();
+        let root_key_node = hive.root_key_node().unwrap();
+
+        // A strict traversal stops at the first error.
+        let mut descendants = root_key_node.descendants(TraversalMode::Strict);
+        assert!(descendants.by_ref().any(|key_node| key_node.is_err()));
+        assert!(descendants.next().is_none());
+        assert_eq!(descendants.skipped(), 0);
+
+        let mut all_values = root_key_node.all_values(TraversalMode::Strict);
+        assert!(all_values.by_ref().any(|key_value| key_value.is_err()));
+        assert!(all_values.next().is_none());
+
+        // A lenient traversal skips the corrupt subkey (and thereby its subtree) and value.
+        let mut descendants = root_key_node.descendants(TraversalMode::Lenient);
+        let lenient_key_count = descendants
+            .by_ref()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+            .len();
+        assert_eq!(lenient_key_count, key_count - corrupt_key_count);
+        assert_eq!(descendants.skipped(), 1);
+
+        let mut all_values = root_key_node.all_values(TraversalMode::Lenient);
+        let lenient_value_count = all_values
+            .by_ref()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+            .len();
+        assert_eq!(lenient_value_count, value_count - corrupt_value_count - 1);
+        assert_eq!(all_values.skipped(), 2);
+
+        // The same applies to sorting values.
+        let data_key_node = root_key_node.subkey("data-test").unwrap().unwrap();
+        assert!(data_key_node.values_sorted().is_err());
+        assert!(data_key_node
+            .values_sorted_with(TraversalMode::Strict)
+            .is_err());
+
+        let (values, skipped) = data_key_node
+            .values_sorted_with(TraversalMode::Lenient)
+            .unwrap();
+        assert_eq!(values.len(), data_value_count - 1);
+        assert_eq!(skipped, 1);
+    }
+}